@@ -0,0 +1,65 @@
+//! Binary to generate the `chains::addresses` module from the deployment manifests in
+//! `contracts/deployments`, by running `cargo run -p hotshot-contract-adapter --bin gen-address-book`.
+//!
+//! Each manifest is a .env file, in the format written by the sequencer `deploy` binary, named
+//! after the chain ID of the L1 it was deployed to.
+
+use ethers::types::Address;
+use hotshot_contract_adapter::chains::parse_manifest;
+use std::{collections::BTreeMap, fs, path::PathBuf, process::Command};
+
+const HEADER: &str = "//! Addresses of contracts deployed on well-known chains.
+//!
+//! This file is generated from the deployment manifests in `contracts/deployments` by running
+//! `cargo run -p hotshot-contract-adapter --bin gen-address-book`. Do not edit it by hand.
+
+use ethers::types::{Address, H160};
+
+/// `(chain ID, [(contract, address)])` for each chain with a deployment manifest.
+";
+
+fn main() {
+    let crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let mut manifests = crate_dir.clone();
+    manifests.pop();
+    manifests.pop();
+    manifests.push("deployments");
+
+    let mut chains = BTreeMap::<u64, BTreeMap<String, Address>>::new();
+    for entry in fs::read_dir(&manifests).expect("failed to read deployment manifests") {
+        let path = entry.unwrap().path();
+        if path.extension() != Some("env".as_ref()) {
+            continue;
+        }
+        let chain_id = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+            .unwrap_or_else(|| panic!("{} is not named after a chain ID", path.display()));
+        let manifest = fs::read_to_string(&path).unwrap();
+        let contracts = parse_manifest(&manifest)
+            .unwrap_or_else(|err| panic!("invalid manifest {}: {err:#}", path.display()));
+        chains.insert(chain_id, contracts);
+    }
+
+    let mut code = HEADER.to_string();
+    code += "pub const ADDRESSES: &[(u64, &[(&str, Address)])] = &[\n";
+    for (chain_id, contracts) in &chains {
+        code += &format!("({chain_id}, &[\n");
+        for (name, address) in contracts {
+            code += &format!("(\"{name}\",\n// {address:#x}\nH160({:?})),\n", address.0);
+        }
+        code += "]),\n";
+    }
+    code += "];\n";
+
+    let path = crate_dir.join("src/chains/addresses.rs");
+    println!("Path:{:?}", path.to_str());
+    fs::write(&path, code).unwrap();
+
+    Command::new("rustfmt")
+        .arg("--edition=2021")
+        .arg(&path)
+        .output()
+        .expect("Failed to format the address book");
+}
@@ -0,0 +1,83 @@
+//! Contract addresses of deployments on well-known chains.
+
+use anyhow::{anyhow, Context};
+use ethers::types::Address;
+use std::collections::BTreeMap;
+
+pub mod addresses;
+
+/// All contract addresses known for chain `chain_id`.
+///
+/// Contracts are identified by the environment variable the deployer writes their address to, e.g.
+/// `ESPRESSO_SEQUENCER_HOTSHOT_ADDRESS`.
+pub fn deployment(chain_id: u64) -> Option<&'static [(&'static str, Address)]> {
+    addresses::ADDRESSES
+        .iter()
+        .find(|(id, _)| *id == chain_id)
+        .map(|(_, contracts)| *contracts)
+}
+
+/// The address of contract `name` on chain `chain_id`, if it is part of a known deployment.
+pub fn address(chain_id: u64, name: &str) -> Option<Address> {
+    deployment(chain_id)?
+        .iter()
+        .find(|(contract, _)| *contract == name)
+        .map(|(_, address)| *address)
+}
+
+/// Parse a deployment manifest in the .env format written by the deployer.
+pub fn parse_manifest(manifest: &str) -> anyhow::Result<BTreeMap<String, Address>> {
+    let mut contracts = BTreeMap::new();
+    for line in manifest.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, address) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("malformed manifest line: {line}"))?;
+        let address = address
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid address for {name}"))?;
+        contracts.insert(name.trim().to_string(), address);
+    }
+    Ok(contracts)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn test_address_book_up_to_date() {
+        let mut dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        dir.pop();
+        dir.pop();
+        dir.push("deployments");
+
+        let mut num_manifests = 0;
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension() != Some("env".as_ref()) {
+                continue;
+            }
+            num_manifests += 1;
+            let chain_id: u64 = path.file_stem().unwrap().to_str().unwrap().parse().unwrap();
+            let manifest = parse_manifest(&fs::read_to_string(&path).unwrap()).unwrap();
+            let deployment = deployment(chain_id).unwrap_or_else(|| {
+                panic!("chain {chain_id} missing from address book, run gen-address-book")
+            });
+            let expected = deployment
+                .iter()
+                .map(|(name, address)| (name.to_string(), *address))
+                .collect::<BTreeMap<_, _>>();
+            assert_eq!(
+                manifest, expected,
+                "address book out of date for chain {chain_id}, run gen-address-book"
+            );
+        }
+        assert_eq!(num_manifests, addresses::ADDRESSES.len());
+    }
+}
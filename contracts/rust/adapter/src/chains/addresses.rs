@@ -0,0 +1,19 @@
+//! Addresses of contracts deployed on well-known chains.
+//!
+//! This file is generated from the deployment manifests in `contracts/deployments` by running
+//! `cargo run -p hotshot-contract-adapter --bin gen-address-book`. Do not edit it by hand.
+
+use ethers::types::{Address, H160};
+
+/// `(chain ID, [(contract, address)])` for each chain with a deployment manifest.
+pub const ADDRESSES: &[(u64, &[(&str, Address)])] = &[(
+    11155111,
+    &[(
+        "ESPRESSO_SEQUENCER_HOTSHOT_ADDRESS",
+        // 0x14ff206c0c3d815c1bc7ad5bd32bc0a1c90dc951
+        H160([
+            20, 255, 32, 108, 12, 61, 129, 92, 27, 199, 173, 91, 211, 43, 192, 161, 201, 13, 201,
+            81,
+        ]),
+    )],
+)];
@@ -1,5 +1,6 @@
 //! Cross-domain (between Solidity and Rust) utilities for type conversion and testing
 
+pub mod chains;
 pub mod jellyfish;
 pub mod light_client;
 
@@ -0,0 +1,330 @@
+//! Benchmark light client state proving on synthetic stake tables.
+//!
+//! For each stake table size, this generates random validator keys and a state signed by all of
+//! them, then measures how long it takes to collect the witness (verifying each signature, as the
+//! prover service does), generate the proof with each thread count, and verify the proof. The
+//! results are written to a JSON report.
+
+use ark_bn254::Bn254;
+use ark_ed_on_bn254::EdwardsConfig;
+use ark_std::rand::{rngs::StdRng, SeedableRng};
+use clap::Parser;
+use ethers::types::U256;
+use hotshot_stake_table::{
+    config::STAKE_TABLE_CAPACITY,
+    vec_based::{config::FieldType, StakeTable},
+};
+use hotshot_state_prover::{generate_state_update_proof, service::load_keys, Proof, ProvingKey};
+use hotshot_types::{
+    light_client::{CircuitField, LightClientState, PublicInput, StateVerKey},
+    traits::stake_table::{SnapshotVersion, StakeTableScheme},
+};
+use jf_plonk::{
+    proof_system::{PlonkKzgSnark, UniversalSNARK},
+    transcript::SolidityTranscript,
+};
+use jf_primitives::{
+    constants::CS_ID_SCHNORR,
+    signatures::{
+        bls_over_bn254::{BLSOverBN254CurveSignatureScheme, VerKey as BLSVerKey},
+        schnorr::Signature,
+        SchnorrSignatureScheme, SignatureScheme,
+    },
+};
+use serde::Serialize;
+use std::{collections::HashMap, fs, path::PathBuf, time::Instant};
+
+#[derive(Parser)]
+struct Args {
+    /// Numbers of validators in the synthetic stake tables, separated by commas.
+    #[clap(long, value_delimiter = ',', default_value = "10,50,100")]
+    stake_table_sizes: Vec<usize>,
+
+    /// Stake table capacity of the prover circuit.
+    ///
+    /// Must be at least the largest stake table size.
+    #[clap(long, default_value_t = STAKE_TABLE_CAPACITY)]
+    stake_table_capacity: usize,
+
+    /// Numbers of threads to prove with, separated by commas.
+    #[clap(long, value_delimiter = ',', default_value = "1,2,4,8")]
+    threads: Vec<usize>,
+
+    /// Number of proofs to generate for each stake table size and thread count.
+    #[clap(long, default_value = "1")]
+    iterations: usize,
+
+    /// Seed for generating the synthetic stake tables and proofs.
+    #[clap(long, default_value = "0")]
+    seed: u64,
+
+    /// File to write the JSON report to.
+    #[clap(short, long, default_value = "prover-bench.json")]
+    output: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+struct Report {
+    stake_table_capacity: usize,
+    /// Time to load the SRS and generate the proving and verifying keys.
+    setup_secs: f64,
+    results: Vec<StakeTableResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct StakeTableResult {
+    stake_table_size: usize,
+    /// Time to verify the signatures and assemble the witness.
+    witness_generation_secs: f64,
+    runs: Vec<ThreadResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct ThreadResult {
+    threads: usize,
+    /// Average time to generate a proof.
+    proving_secs: f64,
+    /// Average time to verify a proof.
+    verification_secs: f64,
+}
+
+/// A synthetic stake table and a state signed by all of its validators.
+struct Setup {
+    stake_table: StakeTable<BLSVerKey, StateVerKey, CircuitField>,
+    state: LightClientState,
+    signatures: HashMap<StateVerKey, Signature<EdwardsConfig>>,
+}
+
+struct Witness {
+    entries: Vec<(StateVerKey, U256)>,
+    signer_bit_vec: Vec<bool>,
+    signatures: Vec<Signature<EdwardsConfig>>,
+    threshold: U256,
+}
+
+fn setup(size: usize, capacity: usize, rng: &mut StdRng) -> Setup {
+    let mut stake_table = StakeTable::new(capacity);
+    let mut keys = vec![];
+    for i in 0..size {
+        let (_, bls_key) = BLSOverBN254CurveSignatureScheme::key_gen(&(), rng).unwrap();
+        let (sign_key, ver_key) = SchnorrSignatureScheme::key_gen(&(), rng).unwrap();
+        stake_table
+            .register(bls_key, U256::from(i + 1), ver_key.clone())
+            .unwrap();
+        keys.push((sign_key, ver_key));
+    }
+    stake_table.advance();
+    stake_table.advance();
+
+    let state = LightClientState {
+        view_number: 100,
+        block_height: 73,
+        block_comm_root: CircuitField::from(1234),
+        fee_ledger_comm: CircuitField::from(5678),
+        stake_table_comm: stake_table
+            .commitment(SnapshotVersion::LastEpochStart)
+            .unwrap(),
+    };
+    let state_msg: [FieldType; 7] = (&state).into();
+    let signatures = keys
+        .into_iter()
+        .map(|(sign_key, ver_key)| {
+            let sig = SchnorrSignatureScheme::<EdwardsConfig>::sign(&(), &sign_key, state_msg, rng)
+                .unwrap();
+            (ver_key, sig)
+        })
+        .collect();
+    Setup {
+        stake_table,
+        state,
+        signatures,
+    }
+}
+
+/// Collect the witness for proving the state, as the prover service does for a signatures bundle.
+fn collect_witness(setup: &Setup) -> Witness {
+    let st = &setup.stake_table;
+    let threshold = st.total_stake(SnapshotVersion::LastEpochStart).unwrap() * 2 / 3;
+    let entries = st
+        .try_iter(SnapshotVersion::LastEpochStart)
+        .unwrap()
+        .map(|(_, stake_amount, state_key)| (state_key, stake_amount))
+        .collect::<Vec<_>>();
+    let state_msg: [FieldType; 7] = (&setup.state).into();
+    let mut signer_bit_vec = vec![false; entries.len()];
+    let mut signatures = vec![Default::default(); entries.len()];
+    for (i, (key, _)) in entries.iter().enumerate() {
+        if let Some(sig) = setup.signatures.get(key) {
+            if key.verify(&state_msg, sig, CS_ID_SCHNORR).is_ok() {
+                signer_bit_vec[i] = true;
+                signatures[i] = sig.clone();
+            }
+        }
+    }
+    Witness {
+        entries,
+        signer_bit_vec,
+        signatures,
+        threshold,
+    }
+}
+
+fn prove(
+    rng: &mut StdRng,
+    pk: &ProvingKey,
+    setup: &Setup,
+    witness: &Witness,
+    capacity: usize,
+) -> (Proof, PublicInput) {
+    generate_state_update_proof::<_, _, _, _>(
+        rng,
+        pk,
+        &witness.entries,
+        &witness.signer_bit_vec,
+        &witness.signatures,
+        &setup.state,
+        &witness.threshold,
+        capacity,
+    )
+    .expect("proof generation failed")
+}
+
+/// Run the benchmarks configured by `args`.
+fn run(args: &Args) -> Report {
+    let capacity = args.stake_table_capacity;
+    if let Some(size) = args.stake_table_sizes.iter().find(|size| **size > capacity) {
+        panic!("stake table size {size} exceeds capacity {capacity}");
+    }
+    let iterations = args.iterations.max(1);
+    let mut rng = StdRng::seed_from_u64(args.seed);
+
+    let start = Instant::now();
+    let (pk, vk) = load_keys(capacity);
+    let mut report = Report {
+        stake_table_capacity: capacity,
+        setup_secs: start.elapsed().as_secs_f64(),
+        results: vec![],
+    };
+
+    for &size in &args.stake_table_sizes {
+        eprintln!("Benchmarking stake table of {size} validators");
+        let setup = setup(size, capacity, &mut rng);
+        let start = Instant::now();
+        let witness = collect_witness(&setup);
+        let mut result = StakeTableResult {
+            stake_table_size: size,
+            witness_generation_secs: start.elapsed().as_secs_f64(),
+            runs: vec![],
+        };
+
+        for &threads in &args.threads {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .expect("cannot build thread pool");
+            let mut proving_secs = 0.;
+            let mut verification_secs = 0.;
+            for _ in 0..iterations {
+                let start = Instant::now();
+                let (proof, pi) = pool.install(|| prove(&mut rng, &pk, &setup, &witness, capacity));
+                proving_secs += start.elapsed().as_secs_f64();
+
+                let start = Instant::now();
+                PlonkKzgSnark::<Bn254>::verify::<SolidityTranscript>(
+                    &vk,
+                    pi.as_ref(),
+                    &proof,
+                    None,
+                )
+                .expect("proof verification failed");
+                verification_secs += start.elapsed().as_secs_f64();
+            }
+            let run = ThreadResult {
+                threads,
+                proving_secs: proving_secs / iterations as f64,
+                verification_secs: verification_secs / iterations as f64,
+            };
+            eprintln!("{run:?}");
+            result.runs.push(run);
+        }
+        report.results.push(result);
+    }
+    report
+}
+
+fn main() {
+    let args = Args::parse();
+    let report = run(&args);
+    let json = serde_json::to_string_pretty(&report).unwrap();
+    fs::write(&args.output, json).expect("cannot write report");
+    eprintln!("Report written to {}", args.output.display());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_collect_witness() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut setup = setup(5, 10, &mut rng);
+        let witness = collect_witness(&setup);
+        assert_eq!(witness.entries.len(), 5);
+        assert_eq!(witness.signer_bit_vec, [true; 5]);
+        // Stakes 1 through 5.
+        assert_eq!(witness.threshold, U256::from(10));
+
+        // Signatures which do not verify are left out of the witness.
+        let (key, _) = witness.entries[2].clone();
+        let other = setup.signatures[&witness.entries[0].0].clone();
+        setup.signatures.insert(key, other);
+        let witness = collect_witness(&setup);
+        assert_eq!(witness.signer_bit_vec, [true, true, false, true, true]);
+    }
+
+    #[test]
+    fn test_run() {
+        let args = Args {
+            stake_table_sizes: vec![3, 5],
+            stake_table_capacity: 10,
+            threads: vec![1, 2],
+            iterations: 1,
+            seed: 0,
+            output: PathBuf::new(),
+        };
+        let report = run(&args);
+        assert_eq!(report.stake_table_capacity, 10);
+        assert_eq!(
+            report
+                .results
+                .iter()
+                .map(|result| result.stake_table_size)
+                .collect::<Vec<_>>(),
+            [3, 5]
+        );
+        for result in &report.results {
+            assert_eq!(
+                result
+                    .runs
+                    .iter()
+                    .map(|run| run.threads)
+                    .collect::<Vec<_>>(),
+                [1, 2]
+            );
+            assert!(result.runs.iter().all(|run| run.proving_secs > 0.));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds capacity")]
+    fn test_run_rejects_oversized_stake_table() {
+        run(&Args {
+            stake_table_sizes: vec![11],
+            stake_table_capacity: 10,
+            threads: vec![1],
+            iterations: 1,
+            seed: 0,
+            output: PathBuf::new(),
+        });
+    }
+}
@@ -3,12 +3,15 @@ use clap::Parser;
 use cld::ClDuration;
 use es_version::SEQUENCER_VERSION;
 use ethers::providers::{Http, Middleware, Provider};
-use ethers::signers::{coins_bip39::English, MnemonicBuilder, Signer};
-use ethers::types::Address;
+use ethers::types::{Address, U256};
 use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
-use hotshot_state_prover::service::{run_prover_once, run_prover_service, StateProverConfig};
+use hotshot_state_prover::{
+    service::{run_prover_once, run_prover_service, LightClientTarget, StateProverConfig},
+    submission::ResubmissionPolicy,
+};
+use sequencer_utils::signer::L1SignerSource;
 use snafu::Snafu;
-use std::{str::FromStr as _, time::Duration};
+use std::{path::PathBuf, str::FromStr as _, time::Duration};
 use url::Url;
 
 #[derive(Parser)]
@@ -42,10 +45,29 @@ struct Args {
     light_client_address: Address,
 
     /// Mnemonic phrase for a funded Ethereum wallet.
-    #[clap(long, env = "ESPRESSO_SEQUENCER_ETH_MNEMONIC", default_value = None)]
-    eth_mnemonic: String,
+    ///
+    /// Required unless the account is held on a Ledger or in AWS KMS.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_ETH_MNEMONIC")]
+    #[cfg_attr(
+        all(feature = "ledger", feature = "aws-kms"),
+        clap(required_unless_present_any = ["eth_ledger", "eth_kms_key_id"])
+    )]
+    #[cfg_attr(
+        all(feature = "ledger", not(feature = "aws-kms")),
+        clap(required_unless_present_any = ["eth_ledger"])
+    )]
+    #[cfg_attr(
+        all(feature = "aws-kms", not(feature = "ledger")),
+        clap(required_unless_present_any = ["eth_kms_key_id"])
+    )]
+    #[cfg_attr(
+        not(any(feature = "ledger", feature = "aws-kms")),
+        clap(required = true)
+    )]
+    eth_mnemonic: Option<String>,
 
-    /// Index of a funded account derived from eth-mnemonic.
+    /// Index of a funded account derived from eth-mnemonic, or of the Ledger Live account with
+    /// eth-ledger.
     #[clap(
         long,
         env = "ESPRESSO_SEQUENCER_STATE_PROVER_ACCOUNT_INDEX",
@@ -53,6 +75,18 @@ struct Args {
     )]
     eth_account_index: u32,
 
+    /// Sign L1 transactions with a Ledger hardware wallet instead of a mnemonic.
+    #[cfg(feature = "ledger")]
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_ETH_LEDGER")]
+    eth_ledger: bool,
+
+    /// ID of an AWS KMS key to sign L1 transactions with instead of a mnemonic.
+    ///
+    /// The AWS region and credentials are read from the environment.
+    #[cfg(feature = "aws-kms")]
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_ETH_KMS_KEY_ID")]
+    eth_kms_key_id: Option<String>,
+
     /// URL of the HotShot orchestrator.
     #[clap(
         short,
@@ -64,13 +98,152 @@ struct Args {
 
     /// If daemon and provided, the service will run a basic HTTP server on the given port.
     ///
-    /// The server provides healthcheck and version endpoints.
+    /// The server provides healthcheck, version, status and metrics endpoints.
     #[clap(short, long, env = "ESPRESSO_PROVER_SERVICE_PORT")]
     pub port: Option<u16>,
 
     /// Stake table capacity for the prover circuit
     #[clap(short, long, env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY", default_value_t = STAKE_TABLE_CAPACITY)]
     pub stake_table_capacity: usize,
+
+    /// Directory in which to cache the proving and verifying keys.
+    ///
+    /// Deriving the keys from the SRS takes several minutes. With a cache directory, this only
+    /// happens on the first start, and later starts load the cached keys.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_KEY_CACHE_DIR")]
+    pub key_cache_dir: Option<PathBuf>,
+
+    /// URL of an external proving service.
+    ///
+    /// If provided, witnesses are sent to this service for proof generation instead of proving
+    /// locally. Returned proofs are verified before they are submitted.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_REMOTE_URL")]
+    pub remote_prover_url: Option<Url>,
+
+    /// Bearer token for authenticating with the external proving service.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_REMOTE_TOKEN")]
+    pub remote_prover_token: Option<String>,
+
+    /// Number of proofs the daemon may generate concurrently.
+    ///
+    /// Collecting signatures and submitting proofs for one state overlaps with proving the next
+    /// regardless. Additional workers only help if proving takes longer than the update interval,
+    /// and each local worker needs its own share of CPU and memory.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_WORKERS", default_value = "1")]
+    pub proving_workers: usize,
+
+    /// Catch up on several missed epochs at once.
+    ///
+    /// By default, the prover schedules each light client update after the previous one lands. In
+    /// skip-ahead mode, it proves the end of every missed epoch and the latest state concurrently,
+    /// and submits them in order. This is most useful with several proving workers.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_SKIP_AHEAD")]
+    pub skip_ahead: bool,
+
+    /// Number of recently generated proofs to cache.
+    ///
+    /// A cached proof is reused if the same state has to be submitted again, for example after a
+    /// restart or an L1 reorg. Set to 0 to disable caching.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_PROOF_CACHE_CAPACITY",
+        default_value = "16"
+    )]
+    pub proof_cache_capacity: usize,
+
+    /// Directory in which to persist cached proofs, so they survive restarts.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_PROOF_CACHE_DIR")]
+    pub proof_cache_dir: Option<PathBuf>,
+
+    /// File in which to checkpoint light client updates still in progress on shutdown.
+    ///
+    /// On the next start, the prover resumes any checkpointed update the light client contract
+    /// still needs, instead of scheduling and proving from scratch. Combine with a proof cache
+    /// directory to also avoid proving again states which were already proven.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_CHECKPOINT_PATH")]
+    pub checkpoint_path: Option<PathBuf>,
+
+    /// Standby priority of this replica, when running several provers for high availability.
+    ///
+    /// The replica which submitted the latest light client update keeps submitting. Other replicas
+    /// stand by until the light client contract has not advanced for FAILOVER_TIMEOUT times their
+    /// priority, then take over. Priority 0 never stands by. Each replica must use a different L1
+    /// account.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_STANDBY_PRIORITY",
+        default_value = "0"
+    )]
+    pub standby_priority: usize,
+
+    /// How long the light client contract must stall before a standby replica takes over.
+    #[clap(long, value_parser = parse_duration, default_value = "30m", env = "ESPRESSO_STATE_PROVER_FAILOVER_TIMEOUT")]
+    pub failover_timeout: Duration,
+
+    /// How long to wait for a light client update to be mined before resubmitting it with higher
+    /// fees.
+    #[clap(long, value_parser = parse_duration, default_value = "3m", env = "ESPRESSO_STATE_PROVER_STUCK_TIMEOUT")]
+    pub stuck_timeout: Duration,
+
+    /// Percentage by which fees are increased each time a stuck update is resubmitted.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_FEE_BUMP_PERCENT",
+        default_value = "20"
+    )]
+    pub fee_bump_percent: u64,
+
+    /// Maximum fee per gas, in gwei, to pay for a light client update.
+    ///
+    /// Stuck updates are not resubmitted with fees above this cap. If not set, fees are not capped.
+    #[clap(long, env = "ESPRESSO_STATE_PROVER_MAX_FEE_GWEI")]
+    pub max_fee_gwei: Option<u64>,
+
+    /// Number of times a stuck light client update is resubmitted before giving up.
+    #[clap(
+        long,
+        env = "ESPRESSO_STATE_PROVER_MAX_RESUBMISSIONS",
+        default_value = "10"
+    )]
+    pub max_resubmissions: usize,
+
+    /// Additional light client contracts to send every update to, as ADDRESS@URL, separated by
+    /// commas.
+    ///
+    /// Each mirror is a LightClient contract deployed with the same genesis state, possibly on
+    /// another chain, where URL is the JSON-RPC provider for that chain. The same account is used
+    /// to submit updates on every chain.
+    #[clap(
+        long = "mirror",
+        env = "ESPRESSO_STATE_PROVER_MIRRORS",
+        value_delimiter = ','
+    )]
+    pub mirrors: Vec<LightClientTarget>,
+}
+
+impl Args {
+    /// Where the key of the prover's L1 account is held.
+    fn signer_source(&self) -> L1SignerSource {
+        #[cfg(feature = "ledger")]
+        if self.eth_ledger {
+            return L1SignerSource::Ledger {
+                index: self.eth_account_index as usize,
+            };
+        }
+        #[cfg(feature = "aws-kms")]
+        if let Some(key_id) = &self.eth_kms_key_id {
+            return L1SignerSource::AwsKms {
+                key_id: key_id.clone(),
+            };
+        }
+        L1SignerSource::Mnemonic {
+            phrase: self
+                .eth_mnemonic
+                .clone()
+                .expect("clap requires a mnemonic when no other signer is given"),
+            index: self.eth_account_index,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Snafu)]
@@ -96,23 +269,39 @@ async fn main() {
     // prepare config for state prover from user options
     let provider = Provider::<Http>::try_from(args.l1_provider.to_string()).unwrap();
     let chain_id = provider.get_chainid().await.unwrap().as_u64();
+    let signer = args
+        .signer_source()
+        .connect(chain_id)
+        .await
+        .expect("error connecting to L1 signer");
     let config = StateProverConfig {
         relay_server: args.relay_server.clone(),
         update_interval: args.update_interval,
         l1_provider: args.l1_provider.clone(),
         light_client_address: args.light_client_address,
-        eth_signing_key: MnemonicBuilder::<English>::default()
-            .phrase(args.eth_mnemonic.as_str())
-            .index(args.eth_account_index)
-            .expect("error building wallet")
-            .build()
-            .expect("error opening wallet")
-            .with_chain_id(chain_id)
-            .signer()
-            .clone(),
+        signer,
         orchestrator_url: args.orchestrator_url,
         port: args.port,
         stake_table_capacity: args.stake_table_capacity,
+        key_cache_dir: args.key_cache_dir,
+        remote_prover_url: args.remote_prover_url,
+        remote_prover_token: args.remote_prover_token,
+        proving_workers: args.proving_workers,
+        skip_ahead: args.skip_ahead,
+        proof_cache_capacity: args.proof_cache_capacity,
+        proof_cache_dir: args.proof_cache_dir,
+        checkpoint_path: args.checkpoint_path,
+        standby_priority: args.standby_priority,
+        failover_timeout: args.failover_timeout,
+        resubmission: ResubmissionPolicy {
+            stuck_timeout: args.stuck_timeout,
+            fee_bump_percent: args.fee_bump_percent,
+            max_fee_per_gas: args
+                .max_fee_gwei
+                .map(|gwei| U256::from(gwei) * U256::exp10(9)),
+            max_resubmissions: args.max_resubmissions,
+        },
+        mirrors: args.mirrors,
     };
 
     if args.daemon {
@@ -123,3 +312,43 @@ async fn main() {
         run_prover_once(config, SEQUENCER_VERSION).await;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use clap::error::ErrorKind;
+
+    fn parse(signer_args: &[&str]) -> Result<Args, clap::Error> {
+        let address = format!("{:?}", Address::zero());
+        let args = ["state-prover", "--light-client-address", address.as_str()];
+        Args::try_parse_from(args.iter().chain(signer_args))
+    }
+
+    #[test]
+    fn test_signer_args() {
+        let args = parse(&["--eth-mnemonic", "test junk"]).unwrap();
+        assert!(matches!(
+            args.signer_source(),
+            L1SignerSource::Mnemonic { phrase, index: 0 } if phrase == "test junk"
+        ));
+
+        // A missing signer is a usage error.
+        let err = match parse(&[]) {
+            Ok(_) => panic!("parsed arguments without a signer"),
+            Err(err) => err,
+        };
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+        assert!(err.to_string().contains("--eth-mnemonic"), "{err}");
+
+        #[cfg(feature = "ledger")]
+        assert!(matches!(
+            parse(&["--eth-ledger"]).unwrap().signer_source(),
+            L1SignerSource::Ledger { index: 0 }
+        ));
+        #[cfg(feature = "aws-kms")]
+        assert!(matches!(
+            parse(&["--eth-kms-key-id", "key"]).unwrap().signer_source(),
+            L1SignerSource::AwsKms { key_id } if key_id == "key"
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Checkpointing of in-progress light client updates across restarts.
+//!
+//! Between being scheduled and landing in the contract, an update goes through witness collection
+//! and minutes of proving. A prover which is restarted in the middle of this, for example by a
+//! deployment, would otherwise start over from the latest signed state, and the light client stays
+//! stale for a full scheduling, proving and submission round after every restart. Worse, the relay
+//! server only keeps recent signatures, so the witness for a missed epoch end may no longer be
+//! available.
+//!
+//! The service records each update from the time it is dispatched for proving until the primary
+//! contract has finalized its state, and writes the records to a checkpoint file when it is shut
+//! down. On startup, the updates from the checkpoint which the contract still needs are dispatched
+//! again before any new state is scheduled. Updates which had already been proven are then served
+//! from the proof cache, if one is configured, rather than proven again.
+
+use crate::schedule::{EpochInfo, ScheduledUpdate};
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Updates dispatched for proving whose states are not yet finalized, and the file they are
+/// checkpointed to.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: PathBuf,
+    pending: Mutex<BTreeMap<usize, ScheduledUpdate>>,
+}
+
+impl Checkpoint {
+    /// Checkpoint pending updates to the file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            pending: Default::default(),
+        }
+    }
+
+    /// The file pending updates are checkpointed to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record an update which has been dispatched for proving.
+    pub fn dispatched(&self, update: &ScheduledUpdate) {
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(update.request.state.block_height, update.clone());
+    }
+
+    /// Forget the updates up to `height`, once the state at `height` is finalized.
+    pub fn finalized(&self, height: usize) {
+        let mut pending = self.pending.lock().unwrap();
+        *pending = pending.split_off(&(height + 1));
+    }
+
+    /// Write the pending updates to the checkpoint file.
+    pub fn save(&self) -> io::Result<()> {
+        let updates = self
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        let bytes = serde_json::to_vec(&updates)?;
+        // Write to a temporary file and move it into place, so that being killed while writing
+        // does not destroy the previous checkpoint.
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &self.path)?;
+        tracing::info!(
+            "checkpointed {} pending updates to {}",
+            updates.len(),
+            self.path.display()
+        );
+        Ok(())
+    }
+
+    /// Read the updates checkpointed by a previous run, in order of height.
+    ///
+    /// A missing or unreadable checkpoint is treated as empty.
+    pub fn load(&self) -> Vec<ScheduledUpdate> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return vec![],
+            Err(err) => {
+                tracing::warn!("cannot read checkpoint {}: {err}", self.path.display());
+                return vec![];
+            }
+        };
+        match serde_json::from_slice::<Vec<ScheduledUpdate>>(&bytes) {
+            Ok(mut updates) => {
+                updates.sort_by_key(|update| update.request.state.block_height);
+                tracing::info!(
+                    "loaded {} checkpointed updates from {}",
+                    updates.len(),
+                    self.path.display()
+                );
+                updates
+            }
+            Err(err) => {
+                tracing::warn!("corrupt checkpoint {}: {err}", self.path.display());
+                vec![]
+            }
+        }
+    }
+}
+
+/// The checkpointed updates which a contract with epoch parameters `info` still needs.
+///
+/// Updates for states the contract has already finalized are dropped, as are updates scheduled for
+/// an epoch the contract has since moved past, which would be verified against the wrong stake
+/// table.
+pub fn still_needed(updates: Vec<ScheduledUpdate>, info: &EpochInfo) -> Vec<ScheduledUpdate> {
+    let verifying_epoch = info.verifying_epoch();
+    updates
+        .into_iter()
+        .filter(|update| {
+            update.request.state.block_height as u64 > info.finalized_height
+                && update.epoch >= verifying_epoch
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_ledger::{MockLedger, MockSystemParam};
+
+    fn heights(updates: &[ScheduledUpdate]) -> Vec<(usize, u64)> {
+        updates
+            .iter()
+            .map(|update| (update.request.state.block_height, update.epoch))
+            .collect()
+    }
+
+    #[test]
+    fn test_checkpoint_roundtrip_and_pruning() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("checkpoint.json");
+        assert!(Checkpoint::new(&path).load().is_empty());
+
+        let mut ledger = MockLedger::init(MockSystemParam::init(10), 5);
+        ledger.elapse_with_block();
+        let request = ledger.gen_witness(None);
+        let update = |height, epoch, epoch_end| {
+            let mut request = request.clone();
+            request.state.block_height = height;
+            ScheduledUpdate {
+                request,
+                epoch,
+                epoch_end,
+            }
+        };
+
+        // A prover catching up in skip-ahead mode, which has finalized the first update.
+        let checkpoint = Checkpoint::new(&path);
+        for update in [
+            update(5, 1, false),
+            update(10, 1, true),
+            update(20, 2, true),
+            update(25, 3, false),
+        ] {
+            checkpoint.dispatched(&update);
+        }
+        checkpoint.finalized(5);
+        checkpoint.save().unwrap();
+
+        let loaded = Checkpoint::new(&path).load();
+        assert_eq!(heights(&loaded), [(10, 1), (20, 2), (25, 3)]);
+        let witness = &loaded[0].request;
+        assert_eq!(witness.signer_bit_vec, request.signer_bit_vec);
+        assert_eq!(witness.threshold, request.threshold);
+
+        // The contract is in the middle of epoch 1, so every update is still needed.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 1,
+            finalized_height: 7,
+        };
+        assert_eq!(
+            heights(&still_needed(loaded.clone(), &info)),
+            heights(&loaded)
+        );
+
+        // Another prover landed the end of epoch 1 while this one was down.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 1,
+            finalized_height: 10,
+        };
+        assert_eq!(
+            heights(&still_needed(loaded.clone(), &info)),
+            [(20, 2), (25, 3)]
+        );
+
+        // Another prover is already past the latest checkpointed state.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 3,
+            finalized_height: 27,
+        };
+        assert!(still_needed(loaded, &info).is_empty());
+
+        // A corrupt checkpoint is ignored.
+        fs::write(&path, b"not json").unwrap();
+        assert!(Checkpoint::new(&path).load().is_empty());
+    }
+}
@@ -376,7 +376,11 @@ where
 #[cfg(test)]
 mod tests {
     use super::{build, GenericLightClientState};
-    use crate::test_utils::{key_pairs_for_testing, stake_table_for_testing};
+    use crate::{
+        mock_ledger::{Adversary, MockLedger, MockSystemParam},
+        remote::ProofRequest,
+        test_utils::{key_pairs_for_testing, stake_table_for_testing},
+    };
     use ark_ed_on_bn254::EdwardsConfig as Config;
     use ethers::types::U256;
     use hotshot_types::traits::stake_table::{SnapshotVersion, StakeTableScheme};
@@ -489,89 +493,6 @@ mod tests {
             .check_circuit_satisfiability(public_inputs.as_ref())
             .is_err());
 
-        // bad path: total weight doesn't meet the threshold
-        // bit vector with total weight 23
-        let bad_bit_vec = [
-            true, true, true, true, true, false, false, true, false, false,
-        ];
-        let bad_bit_masked_sigs = bad_bit_vec
-            .iter()
-            .zip(sigs.iter())
-            .map(|(bit, sig)| {
-                if *bit {
-                    sig.clone()
-                } else {
-                    Signature::<Config>::default()
-                }
-            })
-            .collect::<Vec<_>>();
-
-        let bad_bit_vec = bad_bit_vec
-            .into_iter()
-            .map(|b| if b { F::from(1u64) } else { F::from(0u64) })
-            .collect::<Vec<_>>();
-        let (bad_circuit, public_inputs) = build(
-            &entries,
-            &bad_bit_vec,
-            &bad_bit_masked_sigs,
-            &lightclient_state,
-            &U256::from(25u32),
-            ST_CAPACITY,
-        )
-        .unwrap();
-        assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
-            .is_err());
-
-        // bad path: bad stake table commitment
-        let mut bad_lightclient_state = lightclient_state.clone();
-        bad_lightclient_state.stake_table_comm.1 = F::default();
-        let bad_state_msg: [F; 7] = bad_lightclient_state.clone().into();
-        let sig_for_bad_state = state_keys
-            .iter()
-            .map(|(key, _)| {
-                SchnorrSignatureScheme::<Config>::sign(&(), key, bad_state_msg, &mut prng)
-            })
-            .collect::<Result<Vec<_>, PrimitivesError>>()
-            .unwrap();
-        let (bad_circuit, public_inputs) = build(
-            &entries,
-            &bit_vec,
-            &sig_for_bad_state,
-            &bad_lightclient_state,
-            &U256::from(26u32),
-            ST_CAPACITY,
-        )
-        .unwrap();
-        assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
-            .is_err());
-
-        // bad path: incorrect signatures
-        let mut wrong_light_client_state = lightclient_state.clone();
-        // state with a different qc key commitment
-        wrong_light_client_state.stake_table_comm.0 = F::default();
-        let wrong_state_msg: [F; 7] = wrong_light_client_state.into();
-        let wrong_sigs = state_keys
-            .iter()
-            .map(|(key, _)| {
-                SchnorrSignatureScheme::<Config>::sign(&(), key, wrong_state_msg, &mut prng)
-            })
-            .collect::<Result<Vec<_>, PrimitivesError>>()
-            .unwrap();
-        let (bad_circuit, public_inputs) = build(
-            &entries,
-            &bit_vec,
-            &wrong_sigs,
-            &lightclient_state,
-            &U256::from(26u32),
-            ST_CAPACITY,
-        )
-        .unwrap();
-        assert!(bad_circuit
-            .check_circuit_satisfiability(public_inputs.as_ref())
-            .is_err());
-
         // bad path: overflowing stake table size
         assert!(build(
             &entries,
@@ -583,4 +504,38 @@ mod tests {
         )
         .is_err());
     }
+
+    #[test]
+    fn test_circuit_rejects_adversarial_witnesses() {
+        let mut ledger = MockLedger::init(MockSystemParam::init(10), 5);
+        ledger.elapse_with_block();
+
+        assert!(is_satisfied(&ledger.gen_witness(None)));
+        for adversary in Adversary::ALL {
+            assert!(
+                !is_satisfied(&ledger.gen_witness(Some(adversary))),
+                "{adversary:?}"
+            );
+        }
+    }
+
+    fn is_satisfied(witness: &ProofRequest) -> bool {
+        let bit_vec = witness
+            .signer_bit_vec
+            .iter()
+            .map(|b| if *b { F::from(1u64) } else { F::from(0u64) })
+            .collect::<Vec<_>>();
+        let (circuit, public_inputs) = build(
+            &witness.stake_table,
+            &bit_vec,
+            &witness.signatures,
+            &witness.state,
+            &witness.threshold,
+            witness.stake_table_capacity,
+        )
+        .unwrap();
+        circuit
+            .check_circuit_satisfiability(public_inputs.as_ref())
+            .is_ok()
+    }
 }
@@ -0,0 +1,114 @@
+//! Coordination between replicas of the prover service.
+//!
+//! Several prover replicas may run against the same light client contract for high availability.
+//! To avoid all of them paying for the same updates, replicas coordinate through the contract
+//! itself, without any external lock service: the replica whose account submitted the latest
+//! update is the active prover, and it keeps proving and submitting. Every other replica is a
+//! standby, and only starts proving once the contract has not advanced for its failover timeout.
+//!
+//! Each replica is configured with a standby priority. A replica with priority 0 (the default, and
+//! the behavior of a single prover) never waits. A replica with priority `n` takes over after the
+//! contract has stalled for `n` times the failover timeout, so standbys take over one at a time
+//! rather than all at once. Once a takeover succeeds the new active prover advances the contract and
+//! the remaining standbys go back to waiting. Replicas must use distinct L1 accounts so that the
+//! active prover can be identified.
+
+use crate::service::{prepare_contract, read_contract_state, ProverError, StateProverConfig};
+use anyhow::anyhow;
+use ethers::{providers::Middleware, signers::Signer, types::Address};
+use std::time::{Duration, Instant};
+
+/// Number of L1 blocks to search for the event of the latest light client update.
+const NEW_STATE_LOOKBACK: u64 = 10_000;
+
+/// Decides whether this replica should be proving and submitting updates.
+#[derive(Debug)]
+pub struct Coordinator {
+    priority: usize,
+    failover_timeout: Duration,
+    address: Address,
+    last_height: Option<usize>,
+    last_advance: Instant,
+    active: Option<Address>,
+}
+
+impl Coordinator {
+    /// Coordinate according to the standby priority in `config`.
+    pub fn new(config: &StateProverConfig) -> Self {
+        Self {
+            priority: config.standby_priority,
+            failover_timeout: config.failover_timeout,
+            address: config.signer.address(),
+            last_height: None,
+            last_advance: Instant::now(),
+            active: None,
+        }
+    }
+
+    /// Check whether this replica should update the light client now.
+    pub async fn should_update(&mut self, config: &StateProverConfig) -> Result<bool, ProverError> {
+        if self.priority == 0 {
+            return Ok(true);
+        }
+
+        let height = read_contract_state(config).await?.block_height;
+        if self.last_height != Some(height) {
+            // Only remember the new height once we know who advanced the contract to it, so that a
+            // failed lookup is retried on the next call.
+            let active = last_submitter(config, height as u64).await?;
+            self.last_height = Some(height);
+            self.last_advance = Instant::now();
+            self.active = active;
+            tracing::info!(height, active = ?self.active, "light client contract advanced");
+        }
+        if self.active == Some(self.address) {
+            return Ok(true);
+        }
+
+        let stalled = self.last_advance.elapsed();
+        let timeout = self.failover_timeout * self.priority as u32;
+        if stalled >= timeout {
+            tracing::warn!(
+                ?stalled,
+                active = ?self.active,
+                "light client contract has stalled, taking over as active prover"
+            );
+            Ok(true)
+        } else {
+            tracing::info!(?stalled, ?timeout, active = ?self.active, "standing by");
+            Ok(false)
+        }
+    }
+}
+
+/// The account which submitted the update of the light client contract to `height`, if known.
+async fn last_submitter(
+    config: &StateProverConfig,
+    height: u64,
+) -> Result<Option<Address>, ProverError> {
+    let contract = prepare_contract(config).await?;
+    let l1 = contract.client();
+    let latest = l1
+        .get_block_number()
+        .await
+        .map_err(|err| ProverError::ContractError(anyhow!("{err}")))?
+        .as_u64();
+    let events = contract
+        .new_state_filter()
+        .from_block(latest.saturating_sub(NEW_STATE_LOOKBACK))
+        .query_with_meta()
+        .await
+        .map_err(|err| ProverError::ContractError(err.into()))?;
+    let Some((_, meta)) = events
+        .into_iter()
+        .rev()
+        .find(|(event, _)| event.block_height == height)
+    else {
+        return Ok(None);
+    };
+    let tx = l1
+        .get_transaction(meta.transaction_hash)
+        .await
+        .map_err(|err| ProverError::ContractError(anyhow!("{err}")))?;
+    Ok(tx.map(|tx| tx.from))
+}
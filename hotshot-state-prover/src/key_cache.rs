@@ -0,0 +1,184 @@
+//! On-disk cache of the proving and verifying keys.
+//!
+//! Deriving the keys for the state update circuit means loading the universal SRS from Aztec's
+//! ceremony and preprocessing the circuit with it, which takes several minutes and dominates the
+//! startup time of the prover. The keys depend only on the circuit, so the first run stores them in
+//! a cache directory and later runs read them from there, only touching the SRS if the cache is
+//! missing or corrupt. The proving and verifying keys are stored separately, so a prover which
+//! uses an external proving service only loads the small verifying key.
+//!
+//! Cached keys are named by a hash of the parameters of the circuit they are for: the version of
+//! the circuit, its stake table capacity and the degree of the SRS it needs. Keys cached for any
+//! other circuit, including one from an older version of the prover, are ignored.
+//!
+//! Keys are stored uncompressed, which is faster to load than compressed points, and prefixed with
+//! a hash of their contents, which guards against truncated or corrupted files. They are still
+//! fully validated when they are loaded.
+
+use crate::{
+    service::{load_keys, srs_degree},
+    snark::{ProvingKey, VerifyingKey},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Version of the state update circuit, which is released with this crate.
+const CIRCUIT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const HASH_LEN: usize = 32;
+
+/// Proving and verifying keys for a given stake table capacity, cached in a directory.
+#[derive(Clone, Debug)]
+pub struct KeyCache {
+    dir: PathBuf,
+    stake_table_capacity: usize,
+    /// Hash of the parameters of the circuit, naming its cached keys.
+    circuit: String,
+}
+
+impl KeyCache {
+    /// Use keys cached in `dir` for a circuit with the given stake table capacity.
+    pub fn new(dir: impl Into<PathBuf>, stake_table_capacity: usize) -> Self {
+        let params = format!(
+            "state-update/{CIRCUIT_VERSION}/{stake_table_capacity}/{}",
+            srs_degree(stake_table_capacity)
+        );
+        Self {
+            dir: dir.into(),
+            stake_table_capacity,
+            circuit: blake3::hash(params.as_bytes()).to_hex().to_string(),
+        }
+    }
+
+    /// Load the proving key, deriving and caching the keys if they are not cached yet.
+    pub fn proving_key(&self) -> ProvingKey {
+        match read_key(&self.path("pk")) {
+            Some(pk) => pk,
+            None => self.derive().0,
+        }
+    }
+
+    /// Load the verifying key, deriving and caching the keys if they are not cached yet.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        match read_key(&self.path("vk")) {
+            Some(vk) => vk,
+            None => self.derive().1,
+        }
+    }
+
+    fn derive(&self) -> (ProvingKey, VerifyingKey) {
+        tracing::info!(
+            "no usable cached keys in {}, deriving them from the SRS",
+            self.dir.display()
+        );
+        let (pk, vk) = load_keys(self.stake_table_capacity);
+        if let Err(err) = fs::create_dir_all(&self.dir) {
+            tracing::warn!(
+                "cannot create key cache directory {}: {err}",
+                self.dir.display()
+            );
+            return (pk, vk);
+        }
+        for (kind, res) in [
+            ("proving", write_key(&self.path("pk"), &pk)),
+            ("verifying", write_key(&self.path("vk"), &vk)),
+        ] {
+            if let Err(err) = res {
+                tracing::warn!("cannot cache {kind} key: {err}");
+            }
+        }
+        (pk, vk)
+    }
+
+    fn path(&self, kind: &str) -> PathBuf {
+        self.dir
+            .join(format!("state-update-{}.{kind}", self.circuit))
+    }
+}
+
+/// Read a key cached at `path`, if it exists and is intact.
+fn read_key<T: CanonicalDeserialize>(path: &Path) -> Option<T> {
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            tracing::warn!("cannot read cached key {}: {err}", path.display());
+            return None;
+        }
+    };
+    let Some(contents) = verified_contents(&bytes) else {
+        tracing::warn!("cached key {} is corrupt", path.display());
+        return None;
+    };
+    match T::deserialize_uncompressed(contents) {
+        Ok(key) => {
+            tracing::info!("loaded cached key from {}", path.display());
+            Some(key)
+        }
+        Err(err) => {
+            tracing::warn!("cannot deserialize cached key {}: {err}", path.display());
+            None
+        }
+    }
+}
+
+/// Write `key` to `path`, prefixed with the hash of its serialization.
+fn write_key<T: CanonicalSerialize>(path: &Path, key: &T) -> io::Result<()> {
+    let mut bytes = vec![0; HASH_LEN];
+    key.serialize_uncompressed(&mut bytes)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+    let hash = blake3::hash(&bytes[HASH_LEN..]);
+    bytes[..HASH_LEN].copy_from_slice(hash.as_bytes());
+
+    // Write to a temporary file and move it into place, so that a crash while writing never leaves
+    // a partial key behind.
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, &bytes)?;
+    fs::rename(&tmp, path)
+}
+
+/// The contents of a cached key file, if they match the hash they are prefixed with.
+fn verified_contents(bytes: &[u8]) -> Option<&[u8]> {
+    if bytes.len() < HASH_LEN {
+        return None;
+    }
+    let (hash, contents) = bytes.split_at(HASH_LEN);
+    (blake3::hash(contents).as_bytes() == hash).then_some(contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hotshot_types::light_client::CircuitField;
+
+    #[test]
+    fn test_cached_key_roundtrip_and_corruption() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        assert_eq!(read_key::<Vec<CircuitField>>(&path), None);
+
+        let key = vec![CircuitField::from(1u64), CircuitField::from(2u64)];
+        write_key(&path, &key).unwrap();
+        assert_eq!(read_key::<Vec<CircuitField>>(&path), Some(key));
+
+        // A corrupted or truncated file is rejected rather than deserialized.
+        let mut bytes = fs::read(&path).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        assert_eq!(read_key::<Vec<CircuitField>>(&path), None);
+        fs::write(&path, &bytes[..HASH_LEN - 1]).unwrap();
+        assert_eq!(read_key::<Vec<CircuitField>>(&path), None);
+    }
+
+    #[test]
+    fn test_key_cache_named_by_circuit() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = KeyCache::new(dir.path(), 10);
+        assert_eq!(cache.path("pk"), KeyCache::new(dir.path(), 10).path("pk"));
+        assert_ne!(cache.path("pk"), cache.path("vk"));
+        assert_ne!(cache.path("pk"), KeyCache::new(dir.path(), 20).path("pk"));
+    }
+}
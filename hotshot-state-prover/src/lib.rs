@@ -1,13 +1,31 @@
 //! SNARK-assisted `HotShot` light client state update verification
 
+/// Checkpointing of in-progress light client updates across restarts
+pub mod checkpoint;
 /// State verifier circuit builder
 pub mod circuit;
+/// Coordination between replicas of the prover service
+pub mod coordination;
+/// On-disk cache of the proving and verifying keys
+pub mod key_cache;
+/// Prover service metrics
+pub mod metrics;
 /// Utilities for test
 pub mod mock_ledger;
+/// Caching of generated proofs
+pub mod proof_cache;
+/// Client for external proof generation services
+pub mod remote;
+/// Scheduling of light client updates around epoch boundaries
+pub mod schedule;
 /// Prover service related functionalities
 pub mod service;
 /// SNARK proof generation
 pub mod snark;
+/// Status of the prover service
+pub mod status;
+/// Submission of light client updates to L1
+pub mod submission;
 
 #[cfg(test)]
 mod test_utils;
@@ -0,0 +1,112 @@
+//! Metrics for monitoring the prover service.
+
+use crate::status::ProverStatus;
+use hotshot_types::traits::metrics::{Counter, Gauge, Histogram, Metrics, NoMetrics};
+use std::sync::RwLock;
+
+/// Metrics reported by the prover service.
+#[derive(Debug)]
+pub struct ProverMetrics {
+    /// Time taken to generate each proof, excluding proofs found in the cache.
+    pub proof_generation_duration: Box<dyn Histogram>,
+    /// Number of witnesses waiting for a free proving worker.
+    pub queue_depth: Box<dyn Gauge>,
+    /// Number of light client updates submitted to L1.
+    pub submissions: Box<dyn Counter>,
+    /// Number of light client updates which failed to be submitted or were reverted.
+    pub submission_failures: Box<dyn Counter>,
+    /// Total gas used by successful light client updates.
+    pub gas_used: Box<dyn Counter>,
+    /// Latest HotShot block height with a signed light client state.
+    pub hotshot_height: Box<dyn Gauge>,
+    /// HotShot block height of the state finalized in the light client contract.
+    pub light_client_height: Box<dyn Gauge>,
+    /// Number of HotShot blocks the light client contract is behind.
+    pub lag: Box<dyn Gauge>,
+    status: RwLock<ProverStatus>,
+}
+
+impl ProverMetrics {
+    /// Register prover metrics with `metrics`.
+    pub fn new(metrics: &dyn Metrics) -> Self {
+        Self {
+            proof_generation_duration: metrics
+                .create_histogram("proof_generation_duration".into(), Some("s".into())),
+            queue_depth: metrics.create_gauge("proving_queue_depth".into(), None),
+            submissions: metrics.create_counter("l1_submissions".into(), None),
+            submission_failures: metrics.create_counter("l1_submission_failures".into(), None),
+            gas_used: metrics.create_counter("l1_gas_used".into(), None),
+            hotshot_height: metrics.create_gauge("hotshot_block_height".into(), None),
+            light_client_height: metrics.create_gauge("light_client_block_height".into(), None),
+            lag: metrics.create_gauge("light_client_lag".into(), Some("blocks".into())),
+            status: Default::default(),
+        }
+    }
+
+    /// Record the latest HotShot height and the height of the light client contract.
+    pub fn update_heights(&self, hotshot_height: usize, light_client_height: usize) {
+        self.hotshot_height.set(hotshot_height);
+        self.light_client_height.set(light_client_height);
+        let lag = hotshot_height.saturating_sub(light_client_height);
+        self.lag.set(lag);
+        self.update_status(|status| {
+            status.hotshot_height = Some(hotshot_height);
+            status.light_client_height = Some(light_client_height);
+            status.lag = Some(lag);
+        });
+    }
+
+    /// The current status of the service.
+    pub fn status(&self) -> ProverStatus {
+        self.status.read().unwrap().clone()
+    }
+
+    /// Update the status reported by the service.
+    pub fn update_status(&self, f: impl FnOnce(&mut ProverStatus)) {
+        f(&mut self.status.write().unwrap());
+    }
+}
+
+impl Default for ProverMetrics {
+    fn default() -> Self {
+        Self::new(&NoMetrics)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hotshot_query_service::metrics::PrometheusMetrics;
+    use tide_disco::metrics::Metrics as _;
+
+    #[test]
+    fn test_prometheus_export() {
+        let registry = PrometheusMetrics::default();
+        let metrics = ProverMetrics::new(&registry);
+        metrics.update_heights(12, 7);
+        metrics.submissions.add(2);
+        metrics.submission_failures.add(1);
+        metrics.gas_used.add(21000);
+
+        let exported = registry.export().unwrap();
+        for line in [
+            "hotshot_block_height 12",
+            "light_client_block_height 7",
+            "l1_submissions 2",
+            "l1_submission_failures 1",
+            "l1_gas_used 21000",
+        ] {
+            assert!(exported.lines().any(|l| l == line), "{line}\n{exported}");
+        }
+
+        // The heights are reported in the status as well.
+        let status = metrics.status();
+        assert_eq!(status.hotshot_height, Some(12));
+        assert_eq!(status.light_client_height, Some(7));
+        assert_eq!(status.lag, Some(5));
+
+        // A light client ahead of the latest signed state is not lagging.
+        metrics.update_heights(12, 15);
+        assert_eq!(metrics.status().lag, Some(0));
+    }
+}
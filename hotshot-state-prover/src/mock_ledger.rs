@@ -16,10 +16,12 @@ use hotshot_contract_adapter::jellyfish::{field_to_u256, open_key, u256_to_field
 use hotshot_contract_adapter::light_client::ParsedLightClientState;
 use hotshot_stake_table::vec_based::StakeTable;
 
-use crate::{generate_state_update_proof, preprocess, Proof, VerifyingKey};
+use crate::{generate_state_update_proof, preprocess, remote::ProofRequest, Proof, VerifyingKey};
 use hotshot_types::traits::stake_table::StakeTableScheme;
 use hotshot_types::{
-    light_client::{GenericLightClientState, GenericPublicInput, LightClientState},
+    light_client::{
+        GenericLightClientState, GenericPublicInput, LightClientState, StateSignaturesBundle,
+    },
     traits::stake_table::SnapshotVersion,
 };
 use itertools::izip;
@@ -42,6 +44,26 @@ type SchnorrSignKey = jf_primitives::signatures::schnorr::SignKey<ark_ed_on_bn25
 /// Stake table capacity used for testing
 pub const STAKE_TABLE_CAPACITY: usize = 10;
 
+/// Ways in which a state update generated by `MockLedger` can be invalid
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Adversary {
+    /// A quorum is marked as signers, but their signatures are over a different state
+    InvalidSignatures,
+    /// The signers' combined stake falls short of the quorum threshold
+    InsufficientStake,
+    /// The state commits to a stake table other than the one whose members signed it
+    InconsistentStakeTableComm,
+}
+
+impl Adversary {
+    /// All adversarial behaviors
+    pub const ALL: [Self; 3] = [
+        Self::InvalidSignatures,
+        Self::InsufficientStake,
+        Self::InconsistentStakeTableComm,
+    ];
+}
+
 /// Mock for system parameter of `MockLedger`
 pub struct MockSystemParam {
     /// max capacity of stake table
@@ -198,9 +220,20 @@ impl MockLedger {
         self.sync_stake_table(num_reg, num_exit);
     }
 
-    /// Return the light client state and proof of consensus on this finalized state
-    pub fn gen_state_proof(&mut self) -> (GenericPublicInput<F>, Proof) {
-        let state_msg: [F; 7] = self.state.clone().into();
+    /// Return the witness for proving the current state, made invalid by `adversary` if given
+    pub fn gen_witness(&mut self, adversary: Option<Adversary>) -> ProofRequest {
+        let mut state = self.state.clone();
+        if adversary == Some(Adversary::InconsistentStakeTableComm) {
+            let (adv_qc_keys, adv_state_keys) =
+                key_pairs_for_testing(STAKE_TABLE_CAPACITY, &mut self.rng);
+            let adv_st = stake_table_for_testing(&adv_qc_keys, &adv_state_keys);
+            state.stake_table_comm = adv_st.commitment(SnapshotVersion::LastEpochStart).unwrap();
+        }
+        let mut signed_state = state.clone();
+        if adversary == Some(Adversary::InvalidSignatures) {
+            signed_state.view_number += 1;
+        }
+        let state_msg: [F; 7] = signed_state.into();
 
         let st: Vec<(BLSVerKey, U256, SchnorrVerKey)> = self
             .st
@@ -209,18 +242,29 @@ impl MockLedger {
             .collect();
         let st_size = st.len();
 
-        // find a quorum whose accumulated weights exceed threshold
         let mut bit_vec = vec![false; st_size];
         let mut total_weight = U256::from(0);
-        while total_weight < self.threshold {
-            let signer_idx = self.rng.gen_range(0..st_size);
-            // if already selected, skip to next random sample
-            if bit_vec[signer_idx] {
-                continue;
+        if adversary == Some(Adversary::InsufficientStake) {
+            // take signers in order for as long as they stay below the threshold
+            for (i, (_, amount, _)) in st.iter().enumerate() {
+                if total_weight + *amount >= self.threshold {
+                    break;
+                }
+                bit_vec[i] = true;
+                total_weight += *amount;
             }
+        } else {
+            // find a quorum whose accumulated weights exceed threshold
+            while total_weight < self.threshold {
+                let signer_idx = self.rng.gen_range(0..st_size);
+                // if already selected, skip to next random sample
+                if bit_vec[signer_idx] {
+                    continue;
+                }
 
-            bit_vec[signer_idx] = true;
-            total_weight += st[signer_idx].1;
+                bit_vec[signer_idx] = true;
+                total_weight += st[signer_idx].1;
+            }
         }
 
         let sigs = bit_vec
@@ -241,6 +285,46 @@ impl MockLedger {
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
 
+        ProofRequest {
+            stake_table: st
+                .into_iter()
+                .map(|(_, stake_amount, schnorr_key)| (schnorr_key, stake_amount))
+                .collect(),
+            signer_bit_vec: bit_vec,
+            signatures: sigs,
+            state,
+            threshold: self.threshold,
+            stake_table_capacity: STAKE_TABLE_CAPACITY,
+        }
+    }
+
+    /// Return the signatures a relay server would collect on the current state, made invalid by
+    /// `adversary` if given
+    pub fn gen_signatures_bundle(&mut self, adversary: Option<Adversary>) -> StateSignaturesBundle {
+        let witness = self.gen_witness(adversary);
+        let mut accumulated_weight = U256::from(0);
+        let signatures = izip!(
+            witness.stake_table,
+            witness.signer_bit_vec,
+            witness.signatures
+        )
+        .filter(|(_, signed, _)| *signed)
+        .map(|((schnorr_key, stake_amount), _, sig)| {
+            accumulated_weight += stake_amount;
+            (schnorr_key, sig)
+        })
+        .collect();
+        StateSignaturesBundle {
+            state: witness.state,
+            signatures,
+            accumulated_weight,
+        }
+    }
+
+    /// Return the light client state and proof of consensus on this finalized state
+    pub fn gen_state_proof(&mut self) -> (GenericPublicInput<F>, Proof) {
+        let witness = self.gen_witness(None);
+
         let srs = {
             // load SRS from Aztec's ceremony
             let srs = ark_srs::kzg10::aztec20::setup(2u64.pow(16) as usize + 2)
@@ -256,21 +340,15 @@ impl MockLedger {
         };
         let (pk, _) = preprocess(&srs, STAKE_TABLE_CAPACITY)
             .expect("Fail to preprocess state prover circuit");
-        let stake_table_entries = self
-            .st
-            .try_iter(SnapshotVersion::LastEpochStart)
-            .unwrap()
-            .map(|(_, stake_amount, schnorr_key)| (schnorr_key, stake_amount))
-            .collect::<Vec<_>>();
         let (proof, pi) = generate_state_update_proof::<_, _, _, _>(
             &mut self.rng,
             &pk,
-            &stake_table_entries,
-            &bit_vec,
-            &sigs,
-            &self.state,
-            &self.threshold,
-            STAKE_TABLE_CAPACITY,
+            &witness.stake_table,
+            &witness.signer_bit_vec,
+            &witness.signatures,
+            &witness.state,
+            &witness.threshold,
+            witness.stake_table_capacity,
         )
         .expect("Fail to generate state proof");
         (pi, proof)
@@ -0,0 +1,258 @@
+//! Caching of generated state update proofs.
+//!
+//! Generating a proof takes minutes, so a prover which restarts, or which has to resubmit a state
+//! after its update transaction was dropped in an L1 reorg, should not prove the same state again.
+//! Proofs are keyed by the public input of the circuit, which commits to the new state, the stake
+//! table, and the quorum threshold: any valid proof for the same public input can be submitted in
+//! place of another. The key also includes a hash of the verifying key, so that proofs for a
+//! different circuit, such as one for another stake table capacity or from an older version of the
+//! prover, are never served from a cache directory shared with it.
+//!
+//! The cache keeps a bounded number of the most recent proofs in memory and, if configured with a
+//! directory, mirrors them to disk so they survive restarts.
+
+use crate::{
+    remote::{public_input, ProofRequest},
+    snark::{Proof, VerifyingKey},
+};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use hotshot_types::light_client::PublicInput;
+use std::{
+    collections::VecDeque,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+type CacheKey = [u8; 32];
+
+/// Bounded cache of generated proofs.
+#[derive(Debug)]
+pub struct ProofCache {
+    capacity: usize,
+    dir: Option<PathBuf>,
+    /// Hash of the verifying key of the circuit the cached proofs are for.
+    circuit: CacheKey,
+    inner: Mutex<Entries>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    // Compressed proofs with their insertion sequence numbers, oldest first.
+    proofs: VecDeque<(u64, CacheKey, Vec<u8>)>,
+    next_seq: u64,
+}
+
+impl ProofCache {
+    /// Create a cache holding up to `capacity` proofs verifiable with `vk`.
+    ///
+    /// If `dir` is given, proofs are also stored there, and proofs stored by a previous run are
+    /// loaded. A capacity of 0 disables the cache.
+    pub fn new(capacity: usize, dir: Option<PathBuf>, vk: &VerifyingKey) -> Self {
+        let mut bytes = vec![];
+        vk.serialize_compressed(&mut bytes)
+            .expect("serializing a verifying key cannot fail");
+        Self::for_circuit(capacity, dir, *blake3::hash(&bytes).as_bytes())
+    }
+
+    fn for_circuit(capacity: usize, dir: Option<PathBuf>, circuit: CacheKey) -> Self {
+        let mut entries = Entries::default();
+        if let Some(dir) = &dir {
+            if let Err(err) = fs::create_dir_all(dir) {
+                tracing::warn!(
+                    "cannot create proof cache directory {}: {err}",
+                    dir.display()
+                );
+            }
+            entries = load_dir(dir, capacity);
+            tracing::info!(
+                "loaded {} cached proofs from {}",
+                entries.proofs.len(),
+                dir.display()
+            );
+        }
+        Self {
+            capacity,
+            dir,
+            circuit,
+            inner: Mutex::new(entries),
+        }
+    }
+
+    /// Get a cached proof for `request`, along with its public input.
+    pub fn get(&self, request: &ProofRequest) -> Option<(Proof, PublicInput)> {
+        let bytes = self.get_raw(&self.key(request))?;
+        match Proof::deserialize_compressed(&*bytes) {
+            Ok(proof) => Some((proof, public_input(&request.state, &request.threshold))),
+            Err(err) => {
+                tracing::warn!("corrupt proof in cache: {err}");
+                None
+            }
+        }
+    }
+
+    /// Cache a proof generated for `request`.
+    pub fn insert(&self, request: &ProofRequest, proof: &Proof) {
+        let mut bytes = vec![];
+        if let Err(err) = proof.serialize_compressed(&mut bytes) {
+            tracing::warn!("cannot serialize proof for caching: {err}");
+            return;
+        }
+        self.insert_raw(self.key(request), bytes);
+    }
+
+    /// The cache key for a proof of `request`: a hash of the circuit and its public input.
+    fn key(&self, request: &ProofRequest) -> CacheKey {
+        let pi = public_input(&request.state, &request.threshold);
+        let mut bytes = self.circuit.to_vec();
+        for x in pi.as_ref() {
+            x.serialize_compressed(&mut bytes)
+                .expect("serializing a field element cannot fail");
+        }
+        *blake3::hash(&bytes).as_bytes()
+    }
+
+    fn get_raw(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .proofs
+            .iter()
+            .find(|(_, k, _)| k == key)
+            .map(|(_, _, bytes)| bytes.clone())
+    }
+
+    fn insert_raw(&self, key: CacheKey, bytes: Vec<u8>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = self.inner.lock().unwrap();
+        if inner.proofs.iter().any(|(_, k, _)| *k == key) {
+            return;
+        }
+        while inner.proofs.len() >= self.capacity {
+            let Some((seq, evicted, _)) = inner.proofs.pop_front() else {
+                break;
+            };
+            if let Some(dir) = &self.dir {
+                if let Err(err) = fs::remove_file(entry_path(dir, seq, &evicted)) {
+                    tracing::warn!("cannot remove evicted proof from cache: {err}");
+                }
+            }
+        }
+        let seq = inner.next_seq;
+        inner.next_seq += 1;
+        if let Some(dir) = &self.dir {
+            if let Err(err) = fs::write(entry_path(dir, seq, &key), &bytes) {
+                tracing::warn!("cannot write proof to cache: {err}");
+            }
+        }
+        inner.proofs.push_back((seq, key, bytes));
+    }
+}
+
+// Files are named by sequence number, so the order of insertion can be recovered on restart.
+fn entry_path(dir: &Path, seq: u64, key: &CacheKey) -> PathBuf {
+    dir.join(format!("{seq}-{}.proof", blake3::Hash::from(*key).to_hex()))
+}
+
+fn parse_entry_path(path: &Path) -> Option<(u64, CacheKey)> {
+    if path.extension()? != "proof" {
+        return None;
+    }
+    let (seq, key) = path.file_stem()?.to_str()?.split_once('-')?;
+    Some((
+        seq.parse().ok()?,
+        *blake3::Hash::from_hex(key).ok()?.as_bytes(),
+    ))
+}
+
+/// Load the `capacity` most recent proofs from `dir`, deleting older ones.
+fn load_dir(dir: &Path, capacity: usize) -> Entries {
+    let read_dir = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) => {
+            tracing::warn!("cannot read proof cache directory {}: {err}", dir.display());
+            return Default::default();
+        }
+    };
+    let mut files = read_dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            let (seq, key) = parse_entry_path(&path)?;
+            Some((seq, key, path))
+        })
+        .collect::<Vec<_>>();
+    files.sort_by_key(|(seq, _, _)| *seq);
+
+    let mut entries = Entries {
+        next_seq: files.last().map_or(0, |(seq, _, _)| seq + 1),
+        ..Default::default()
+    };
+    let stale = files.len().saturating_sub(capacity);
+    for (i, (seq, key, path)) in files.into_iter().enumerate() {
+        if i < stale {
+            if let Err(err) = fs::remove_file(&path) {
+                tracing::warn!("cannot remove stale proof {}: {err}", path.display());
+            }
+            continue;
+        }
+        match fs::read(&path) {
+            Ok(bytes) => entries.proofs.push_back((seq, key, bytes)),
+            Err(err) => tracing::warn!("cannot read cached proof {}: {err}", path.display()),
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_ledger::{MockLedger, MockSystemParam};
+
+    #[test]
+    fn test_proof_cache_eviction_and_reload() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache = ProofCache::for_circuit(2, Some(dir.path().into()), [0; 32]);
+        cache.insert_raw([0; 32], vec![0]);
+        cache.insert_raw([1; 32], vec![1]);
+        cache.insert_raw([2; 32], vec![2]);
+
+        // The oldest proof is evicted, both in memory and on disk.
+        assert_eq!(cache.get_raw(&[0; 32]), None);
+        assert_eq!(cache.get_raw(&[1; 32]), Some(vec![1]));
+        assert_eq!(cache.get_raw(&[2; 32]), Some(vec![2]));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // Proofs survive a restart.
+        let cache = ProofCache::for_circuit(2, Some(dir.path().into()), [0; 32]);
+        assert_eq!(cache.get_raw(&[1; 32]), Some(vec![1]));
+        assert_eq!(cache.get_raw(&[2; 32]), Some(vec![2]));
+
+        // Restarting with a smaller capacity drops excess proofs.
+        let cache = ProofCache::for_circuit(1, Some(dir.path().into()), [0; 32]);
+        assert_eq!(cache.get_raw(&[2; 32]), Some(vec![2]));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // A capacity of 0 disables caching.
+        let cache = ProofCache::for_circuit(0, None, [0; 32]);
+        cache.insert_raw([0; 32], vec![0]);
+        assert_eq!(cache.get_raw(&[0; 32]), None);
+    }
+
+    #[test]
+    fn test_proof_cache_key_depends_on_circuit() {
+        let mut ledger = MockLedger::init(MockSystemParam::init(10), 5);
+        ledger.elapse_with_block();
+        let request = ledger.gen_witness(None);
+        let mut newer = ledger.gen_witness(None);
+        newer.state.block_height += 1;
+
+        let cache = ProofCache::for_circuit(1, None, [0; 32]);
+        assert_eq!(cache.key(&request), cache.key(&request));
+        assert_ne!(cache.key(&request), cache.key(&newer));
+
+        // A cache directory shared with a prover for another circuit does not serve its proofs.
+        let other = ProofCache::for_circuit(1, None, [1; 32]);
+        assert_ne!(cache.key(&request), other.key(&request));
+    }
+}
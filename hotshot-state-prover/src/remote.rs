@@ -0,0 +1,241 @@
+//! Offloading proof generation to an external proving service.
+//!
+//! Proving is by far the most expensive part of a light client state update. Operators may run it
+//! on dedicated (e.g. GPU) machines while the prover service keeps collecting signatures and
+//! submitting proofs locally. The witness for each update is sent to the remote service as a
+//! [`ProofRequest`] via `POST {url}/prove`, and the service responds with a [`ProofResponse`].
+//!
+//! Returned proofs are never trusted blindly: each one is verified against the local verifying key
+//! and the public input derived from the requested state before it is submitted to L1.
+
+use crate::{
+    service::ProverError,
+    snark::{Proof, VerifyingKey},
+};
+use ark_bn254::Bn254;
+use ark_serialize::CanonicalDeserialize;
+use ethers::types::U256;
+use hotshot_contract_adapter::jellyfish::u256_to_field;
+use hotshot_types::light_client::{
+    CircuitField, GenericPublicInput, LightClientState, PublicInput, StateSignature, StateVerKey,
+};
+use jf_plonk::{
+    proof_system::{PlonkKzgSnark, UniversalSNARK},
+    transcript::SolidityTranscript,
+};
+use serde::{Deserialize, Serialize};
+use surf_disco::Client;
+use tide_disco::error::ServerError;
+use url::Url;
+use vbs::version::StaticVersionType;
+
+/// Witness for a single light client state update.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofRequest {
+    /// Stake table entries `(state key, stake amount)` of the voting stake table.
+    pub stake_table: Vec<(StateVerKey, U256)>,
+    /// Which stake table entries signed the new state.
+    pub signer_bit_vec: Vec<bool>,
+    /// Signatures over the new state, default for entries which did not sign.
+    pub signatures: Vec<StateSignature>,
+    /// The new light client state.
+    pub state: LightClientState,
+    /// Quorum threshold of the voting stake table.
+    pub threshold: U256,
+    /// Stake table capacity of the circuit.
+    pub stake_table_capacity: usize,
+}
+
+/// Response of a remote proving service.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofResponse {
+    /// Compressed canonical serialization of the PLONK proof.
+    pub proof: Vec<u8>,
+}
+
+/// Client for an external proving service.
+pub struct RemoteProver<Ver: StaticVersionType> {
+    client: Client<ServerError, Ver>,
+    token: Option<String>,
+    vk: VerifyingKey,
+}
+
+impl<Ver: StaticVersionType> RemoteProver<Ver> {
+    /// Connect to the proving service at `url`.
+    ///
+    /// If `token` is provided, it is sent with each request as a bearer token. `vk` is used to
+    /// check the integrity of returned proofs.
+    pub fn new(url: Url, token: Option<String>, vk: VerifyingKey) -> Self {
+        Self {
+            client: Client::new(url),
+            token,
+            vk,
+        }
+    }
+
+    /// The verifying key remote proofs are checked against.
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.vk
+    }
+
+    /// Generate a proof for `request` remotely.
+    ///
+    /// Returns the proof along with the public input it was verified against.
+    pub async fn prove(&self, request: &ProofRequest) -> Result<(Proof, PublicInput), ProverError> {
+        let mut req = self
+            .client
+            .post::<ProofResponse>("prove")
+            .body_json(request)
+            .map_err(ProverError::RemoteProverError)?;
+        if let Some(token) = &self.token {
+            req = req.header("Authorization", format!("Bearer {token}"));
+        }
+        let response = req.send().await.map_err(ProverError::RemoteProverError)?;
+
+        let proof = Proof::deserialize_compressed(&*response.proof)
+            .map_err(|err| ProverError::InvalidRemoteProof(err.to_string()))?;
+        let public_input = public_input(&request.state, &request.threshold);
+        PlonkKzgSnark::<Bn254>::verify::<SolidityTranscript>(
+            &self.vk,
+            public_input.as_ref(),
+            &proof,
+            None,
+        )
+        .map_err(|err| ProverError::InvalidRemoteProof(err.to_string()))?;
+
+        Ok((proof, public_input))
+    }
+}
+
+/// The public input of the state update circuit for `state` under quorum `threshold`.
+pub(crate) fn public_input(state: &LightClientState, threshold: &U256) -> PublicInput {
+    let pi = vec![
+        u256_to_field(*threshold),
+        CircuitField::from(state.view_number as u64),
+        CircuitField::from(state.block_height as u64),
+        state.block_comm_root,
+        state.fee_ledger_comm,
+        state.stake_table_comm.0,
+        state.stake_table_comm.1,
+        state.stake_table_comm.2,
+    ];
+    GenericPublicInput::from(pi)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        generate_state_update_proof,
+        mock_ledger::{MockLedger, MockSystemParam, STAKE_TABLE_CAPACITY},
+        service::load_keys,
+    };
+    use ark_serialize::CanonicalSerialize;
+    use async_std::{sync::RwLock, task::spawn};
+    use es_version::{SequencerVersion, SEQUENCER_VERSION};
+    use futures::FutureExt;
+    use jf_utils::test_rng;
+    use portpicker::pick_unused_port;
+    use std::sync::{Arc, Mutex};
+    use tide_disco::{Api, App};
+
+    /// A proving service which responds to every request with the same bytes.
+    #[derive(Clone, Default)]
+    struct StubProver {
+        response: Vec<u8>,
+        /// The `Authorization` header of each request received.
+        authorization: Arc<Mutex<Vec<Option<String>>>>,
+    }
+
+    /// Serve `stub` on a free port, returning the URL of the proving service.
+    fn serve_stub(stub: StubProver) -> Url {
+        let toml = toml::from_str::<toml::Value>(
+            r#"
+            [route.prove]
+            PATH = ["/prove"]
+            METHOD = "POST"
+            DOC = "Generate a proof for a light client state update."
+            "#,
+        )
+        .unwrap();
+        let mut api = Api::<RwLock<StubProver>, ServerError, SequencerVersion>::new(toml).unwrap();
+        api.post("prove", |req, stub| {
+            async move {
+                let authorization = req.header("Authorization").map(|value| value.to_string());
+                stub.authorization.lock().unwrap().push(authorization);
+                Ok(ProofResponse {
+                    proof: stub.response.clone(),
+                })
+            }
+            .boxed()
+        })
+        .unwrap();
+        let mut app = App::<_, ServerError>::with_state(RwLock::new(stub));
+        app.register_module("prover", api).unwrap();
+        let port = pick_unused_port().unwrap();
+        spawn(app.serve(format!("0.0.0.0:{port}"), SEQUENCER_VERSION));
+        format!("http://localhost:{port}/prover").parse().unwrap()
+    }
+
+    #[async_std::test]
+    async fn test_remote_prover() {
+        let mut ledger = MockLedger::init(MockSystemParam::init(10), 5);
+        ledger.elapse_with_block();
+        let request = ledger.gen_witness(None);
+        let (pk, vk) = load_keys(STAKE_TABLE_CAPACITY);
+        let (proof, _) = generate_state_update_proof::<_, _, _, _>(
+            &mut test_rng(),
+            &pk,
+            &request.stake_table,
+            &request.signer_bit_vec,
+            &request.signatures,
+            &request.state,
+            &request.threshold,
+            request.stake_table_capacity,
+        )
+        .unwrap();
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+
+        let stub = StubProver {
+            response: bytes,
+            ..Default::default()
+        };
+        let url = serve_stub(stub.clone());
+        let prover =
+            RemoteProver::<SequencerVersion>::new(url.clone(), Some("secret".into()), vk.clone());
+        assert!(prover.client.connect(None).await);
+
+        // A valid proof is accepted, and the token is sent with the request.
+        let (_, public_input) = prover.prove(&request).await.unwrap();
+        assert_eq!(
+            public_input.as_ref(),
+            super::public_input(&request.state, &request.threshold).as_ref()
+        );
+        assert_eq!(
+            *stub.authorization.lock().unwrap(),
+            [Some("Bearer secret".to_string())]
+        );
+
+        // A proof of a different state than the one requested is rejected.
+        let mut other = request.clone();
+        other.state.block_height += 1;
+        let err = prover.prove(&other).await.unwrap_err();
+        assert!(matches!(err, ProverError::InvalidRemoteProof(_)), "{err}");
+
+        // Without a token, no authorization is sent.
+        let prover = RemoteProver::<SequencerVersion>::new(url, None, vk.clone());
+        prover.prove(&request).await.unwrap();
+        assert_eq!(stub.authorization.lock().unwrap().last(), Some(&None));
+
+        // A response which is not a proof at all is rejected.
+        let corrupt = StubProver {
+            response: b"not a proof".to_vec(),
+            ..Default::default()
+        };
+        let prover = RemoteProver::<SequencerVersion>::new(serve_stub(corrupt), None, vk);
+        assert!(prover.client.connect(None).await);
+        let err = prover.prove(&request).await.unwrap_err();
+        assert!(matches!(err, ProverError::InvalidRemoteProof(_)), "{err}");
+    }
+}
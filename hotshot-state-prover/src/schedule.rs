@@ -0,0 +1,233 @@
+//! Scheduling of light client updates around epoch boundaries.
+//!
+//! The light client contract only accepts a state past the end of the current epoch once the state
+//! at the last block of the epoch has been submitted, since that state fixes the stake table which
+//! verifies the next epoch. If the prover only ever proved the latest signed state, the contract
+//! would be stuck as soon as the latest state crossed an epoch boundary. Instead, whenever the
+//! latest signed state is at or past the end of the epoch, the prover proves the state at the end
+//! of the epoch, ahead of any routine update.
+//!
+//! Each scheduled update records the epoch it was scheduled for. If the contract moves to a new
+//! epoch while a proof is still queued, for example because another prover submitted the end of
+//! the epoch first, the queued proof is dropped rather than submitted against the wrong stake table.
+//!
+//! By default, the prover waits for each update to land before scheduling the next, so a prover
+//! which has fallen several epochs behind catches up one epoch per proving and submission round
+//! trip. In skip-ahead mode, it instead schedules the next update as if the states already handed
+//! to provers had landed (see [`EpochInfo::ahead_of`]). The ends of all the missed epochs and the
+//! latest state are then proven concurrently, and submitted in order as each one becomes valid.
+//! The contract never accepts a state past an epoch it has not seen the end of, so skipping over the
+//! epoch ends themselves is not possible.
+
+use crate::{
+    remote::ProofRequest,
+    service::{prepare_contract, ProverError, StateProverConfig},
+};
+use hotshot_contract_adapter::light_client::ParsedLightClientState;
+use serde::{Deserialize, Serialize};
+
+/// Epoch parameters of the light client contract.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochInfo {
+    /// Number of blocks in each epoch.
+    pub blocks_per_epoch: u64,
+    /// The current epoch of the contract.
+    pub current_epoch: u64,
+    /// Block height of the finalized state in the contract.
+    pub finalized_height: u64,
+}
+
+/// Which state the prover should prove next.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// The contract is up to date with the latest signed state.
+    UpToDate,
+    /// Prove the latest signed state.
+    Latest,
+    /// Prove the state at the last block of the epoch, at the given height.
+    EpochEnd(u64),
+}
+
+/// A state scheduled for proving.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduledUpdate {
+    /// The witness for the proof.
+    pub request: ProofRequest,
+    /// The epoch in which the update was scheduled, see [`EpochInfo::verifying_epoch`].
+    pub epoch: u64,
+    /// Whether this is the update for the last block of the epoch.
+    pub epoch_end: bool,
+}
+
+impl EpochInfo {
+    /// Read the epoch parameters of the light client contract.
+    pub async fn fetch(config: &StateProverConfig) -> Result<Self, ProverError> {
+        let contract = prepare_contract(config).await?;
+        let blocks_per_epoch = contract
+            .blocks_per_epoch()
+            .call()
+            .await
+            .map_err(|err| ProverError::ContractError(err.into()))?;
+        let current_epoch = contract
+            .current_epoch()
+            .call()
+            .await
+            .map_err(|err| ProverError::ContractError(err.into()))?;
+        let finalized: ParsedLightClientState = contract
+            .get_finalized_state()
+            .call()
+            .await
+            .map_err(|err| ProverError::ContractError(err.into()))?
+            .into();
+        Ok(Self {
+            blocks_per_epoch: blocks_per_epoch.into(),
+            current_epoch,
+            finalized_height: finalized.block_height,
+        })
+    }
+
+    /// The epoch which the next update to the contract belongs to.
+    ///
+    /// Once the state at the end of the current epoch is finalized, the contract advances to the
+    /// next epoch with the following update.
+    pub fn verifying_epoch(&self) -> u64 {
+        if self.finalized_height == self.current_epoch.saturating_mul(self.blocks_per_epoch) {
+            self.current_epoch + 1
+        } else {
+            self.current_epoch
+        }
+    }
+
+    /// Height of the last block of the epoch which the next update belongs to.
+    ///
+    /// The contract rejects any later state until the state at this height has been submitted.
+    pub fn next_epoch_end(&self) -> u64 {
+        self.verifying_epoch().saturating_mul(self.blocks_per_epoch)
+    }
+
+    /// The epoch parameters the contract will have once the state at `height` is finalized.
+    ///
+    /// `height` must be a state the contract will accept after the states scheduled so far, i.e.
+    /// it must not skip past the end of an epoch. If the contract is already at or past `height`,
+    /// this is the current state of the contract.
+    pub fn ahead_of(&self, height: u64) -> Self {
+        if height <= self.finalized_height {
+            return *self;
+        }
+        let epoch_end = self.next_epoch_end();
+        let current_epoch = if height <= epoch_end {
+            self.verifying_epoch()
+        } else {
+            self.verifying_epoch() + (height - epoch_end).div_ceil(self.blocks_per_epoch)
+        };
+        Self {
+            blocks_per_epoch: self.blocks_per_epoch,
+            current_epoch,
+            finalized_height: height,
+        }
+    }
+
+    /// Decide which state to prove, given the height of the latest signed state.
+    pub fn schedule(&self, latest_height: u64) -> Target {
+        if latest_height <= self.finalized_height {
+            return Target::UpToDate;
+        }
+        let epoch_end = self.next_epoch_end();
+        if epoch_end > self.finalized_height && latest_height >= epoch_end {
+            Target::EpochEnd(epoch_end)
+        } else {
+            Target::Latest
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_epoch_schedule() {
+        // A freshly deployed contract has finalized the end of epoch 0, the genesis state.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 0,
+            finalized_height: 0,
+        };
+        assert_eq!(info.verifying_epoch(), 1);
+        assert_eq!(info.schedule(0), Target::UpToDate);
+        assert_eq!(info.schedule(5), Target::Latest);
+        assert_eq!(info.schedule(10), Target::EpochEnd(10));
+        assert_eq!(info.schedule(25), Target::EpochEnd(10));
+
+        // Within an epoch, the end of the epoch must be proven before any later state.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 1,
+            finalized_height: 5,
+        };
+        assert_eq!(info.verifying_epoch(), 1);
+        assert_eq!(info.schedule(9), Target::Latest);
+        assert_eq!(info.schedule(25), Target::EpochEnd(10));
+
+        // Once the end of the epoch is finalized, updates belong to the next epoch.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 1,
+            finalized_height: 10,
+        };
+        assert_eq!(info.verifying_epoch(), 2);
+        assert_eq!(info.schedule(15), Target::Latest);
+        assert_eq!(info.schedule(25), Target::EpochEnd(20));
+
+        // Contracts deployed without epochs never reach the end of one.
+        let info = EpochInfo {
+            blocks_per_epoch: u32::MAX.into(),
+            current_epoch: 1,
+            finalized_height: 100,
+        };
+        assert_eq!(info.schedule(1_000_000), Target::Latest);
+    }
+
+    #[test]
+    fn test_skip_ahead_schedule() {
+        // The contract is in the middle of epoch 1, and the latest state is in epoch 4.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 1,
+            finalized_height: 5,
+        };
+        let latest = 35;
+
+        // Each missed epoch end is scheduled in turn without waiting for the previous one to land,
+        // followed by the latest state.
+        let mut dispatched = info.finalized_height;
+        let mut scheduled = vec![];
+        loop {
+            let ahead = info.ahead_of(dispatched);
+            let height = match ahead.schedule(latest) {
+                Target::UpToDate => break,
+                Target::Latest => latest,
+                Target::EpochEnd(height) => height,
+            };
+            scheduled.push((height, ahead.verifying_epoch()));
+            dispatched = height;
+        }
+        assert_eq!(scheduled, [(10, 1), (20, 2), (30, 3), (35, 4)]);
+
+        // States the contract already has do not change the schedule.
+        assert_eq!(info.ahead_of(3), info);
+
+        // If an update fails, the state just before it is the last one dispatched, and the same
+        // update is scheduled again.
+        assert_eq!(info.ahead_of(19).schedule(latest), Target::EpochEnd(20));
+        assert_eq!(info.ahead_of(19).verifying_epoch(), 2);
+
+        // Within the current epoch, the prover skips straight to the latest state.
+        let info = EpochInfo {
+            blocks_per_epoch: 10,
+            current_epoch: 4,
+            finalized_height: 31,
+        };
+        assert_eq!(info.ahead_of(31).schedule(39), Target::Latest);
+    }
+}
@@ -1,26 +1,39 @@
 //! A light client prover service
 
-use crate::snark::{generate_state_update_proof, Proof, ProvingKey};
+use crate::{
+    checkpoint::{still_needed, Checkpoint},
+    coordination::Coordinator,
+    key_cache::KeyCache,
+    metrics::ProverMetrics,
+    proof_cache::ProofCache,
+    remote::{ProofRequest, RemoteProver},
+    schedule::{EpochInfo, ScheduledUpdate, Target},
+    snark::{generate_state_update_proof, Proof, ProvingKey, VerifyingKey},
+    status::{ProverStatus, SrsStatus},
+    submission::{send_update, ResubmissionPolicy},
+};
 use anyhow::anyhow;
 use async_std::{
+    channel::{self, Receiver, Sender, TrySendError},
+    future::timeout,
     io,
-    sync::Arc,
-    task::{sleep, spawn},
+    sync::{Arc, RwLock},
+    task::{block_on, sleep, spawn, spawn_blocking},
 };
-use contract_bindings::light_client::{LightClient, LightClientErrors};
+use contract_bindings::light_client::LightClient;
 use displaydoc::Display;
 use ethers::{
-    core::k256::ecdsa::SigningKey,
     middleware::SignerMiddleware,
     providers::Http,
     providers::{Middleware, Provider, ProviderError},
-    signers::{LocalWallet, Signer, Wallet},
-    types::{Address, U256},
+    signers::Signer,
+    types::{Address, TransactionReceipt, U256},
 };
-use futures::FutureExt;
+use futures::{FutureExt, StreamExt};
 use hotshot_contract_adapter::jellyfish::{u256_to_field, ParsedPlonkProof};
 use hotshot_contract_adapter::light_client::ParsedLightClientState;
 use hotshot_orchestrator::OrchestratorVersion;
+use hotshot_query_service::metrics::PrometheusMetrics;
 use hotshot_stake_table::vec_based::config::FieldType;
 use hotshot_stake_table::vec_based::StakeTable;
 use hotshot_types::signature_key::BLSPubKey;
@@ -37,20 +50,31 @@ use jf_plonk::errors::PlonkError;
 use jf_primitives::constants::CS_ID_SCHNORR;
 use jf_primitives::pcs::prelude::UnivariateUniversalParams;
 use jf_relation::Circuit as _;
+use sequencer_utils::signer::L1Signer;
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook_async_std::Signals;
 use std::{
-    iter,
+    borrow::Cow,
+    collections::{BTreeMap, VecDeque},
+    iter, mem,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
     time::{Duration, Instant},
 };
 use surf_disco::Client;
-use tide_disco::{error::ServerError, Api};
+use tide_disco::{error::ServerError, Api, Error as _, StatusCode};
 use time::ext::InstantExt;
 use url::Url;
 use vbs::version::StaticVersionType;
 
 type F = ark_ed_on_bn254::Fq;
 
-/// A wallet with local signer and connected to network via http
-pub type L1Wallet = SignerMiddleware<Provider<Http>, LocalWallet>;
+/// Maximum number of proofs a submission task holds while waiting for the contract to advance.
+const MAX_DEFERRED_PROOFS: usize = 32;
+
+/// A wallet connected to the network via http, signing with the configured [`L1Signer`]
+pub type L1Wallet = SignerMiddleware<Provider<Http>, L1Signer>;
 
 type NetworkConfig = hotshot_orchestrator::config::NetworkConfig<
     BLSPubKey,
@@ -68,16 +92,168 @@ pub struct StateProverConfig {
     pub l1_provider: Url,
     /// Address of LightClient contract on layer 1.
     pub light_client_address: Address,
-    /// Transaction signing key for Ethereum
-    pub eth_signing_key: SigningKey,
+    /// Transaction signer for Ethereum
+    pub signer: L1Signer,
     /// Address off the hotshot orchestrator, used for stake table initialization.
     pub orchestrator_url: Url,
     /// If daemon and provided, the service will run a basic HTTP server on the given port.
     ///
-    /// The server provides healthcheck and version endpoints.
+    /// The server provides healthcheck, version, status and metrics endpoints.
     pub port: Option<u16>,
     /// Stake table capacity for the prover circuit.
     pub stake_table_capacity: usize,
+    /// Directory in which to cache the proving and verifying keys, so they are only derived from
+    /// the SRS on the first start.
+    pub key_cache_dir: Option<PathBuf>,
+    /// URL of an external proving service.
+    ///
+    /// If provided, proofs are generated by this service instead of locally.
+    pub remote_prover_url: Option<Url>,
+    /// Bearer token used to authenticate with the external proving service.
+    pub remote_prover_token: Option<String>,
+    /// Number of proofs the service may generate concurrently.
+    pub proving_workers: usize,
+    /// Whether to schedule updates past the ones still being proven or submitted, so that a prover
+    /// which is several epochs behind catches up in one round. See [`crate::schedule`].
+    pub skip_ahead: bool,
+    /// Number of recently generated proofs to keep, so they are not regenerated. 0 disables the
+    /// cache.
+    pub proof_cache_capacity: usize,
+    /// Directory in which to persist cached proofs across restarts.
+    pub proof_cache_dir: Option<PathBuf>,
+    /// File to which updates still in progress are written on shutdown, and from which they are
+    /// resumed on startup. See [`crate::checkpoint`].
+    pub checkpoint_path: Option<PathBuf>,
+    /// Standby priority of this replica when running several provers for high availability.
+    ///
+    /// 0 means this replica always updates the light client. See [`crate::coordination`].
+    pub standby_priority: usize,
+    /// How long the light client contract must stall before the standby with priority 1 takes over.
+    pub failover_timeout: Duration,
+    /// How light client updates which are not mined in time are resubmitted.
+    pub resubmission: ResubmissionPolicy,
+    /// Additional light client contracts, possibly on other chains, which mirror the one at
+    /// `light_client_address` and are sent every update.
+    pub mirrors: Vec<LightClientTarget>,
+}
+
+/// A light client contract which the prover updates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightClientTarget {
+    /// URL of the JSON-RPC provider for the chain the contract is deployed on.
+    pub provider: Url,
+    /// Address of the LightClient contract.
+    pub address: Address,
+}
+
+impl FromStr for LightClientTarget {
+    type Err = anyhow::Error;
+
+    /// Parse a target of the form `ADDRESS@URL`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, provider) = s
+            .split_once('@')
+            .ok_or_else(|| anyhow!("expected ADDRESS@URL, got {s}"))?;
+        Ok(Self {
+            provider: provider.parse()?,
+            address: address.parse()?,
+        })
+    }
+}
+
+impl StateProverConfig {
+    /// All light client contracts to update: the primary contract, followed by any mirrors.
+    pub fn targets(&self) -> impl Iterator<Item = LightClientTarget> + '_ {
+        iter::once(LightClientTarget {
+            provider: self.l1_provider.clone(),
+            address: self.light_client_address,
+        })
+        .chain(self.mirrors.iter().cloned())
+    }
+
+    /// This configuration, pointed at `target` instead of the primary contract.
+    pub fn for_target(&self, target: &LightClientTarget) -> Self {
+        Self {
+            l1_provider: target.provider.clone(),
+            light_client_address: target.address,
+            mirrors: vec![],
+            ..self.clone()
+        }
+    }
+}
+
+/// Where SNARK proofs for light client state updates are generated.
+pub enum ProvingBackend<Ver: StaticVersionType> {
+    /// Generate proofs in-process using a local proving key and its verifying key.
+    Local(ProvingKey, VerifyingKey),
+    /// Dispatch witnesses to an external proving service.
+    Remote(RemoteProver<Ver>),
+    /// Serve proofs generated ahead of time, to test the pipeline without proving.
+    #[cfg(test)]
+    Mock(test::MockProver),
+}
+
+impl<Ver: StaticVersionType> ProvingBackend<Ver> {
+    /// Set up the proving backend selected by `config`.
+    pub fn init(config: &StateProverConfig) -> Self {
+        match &config.remote_prover_url {
+            Some(url) => {
+                tracing::info!("Using remote prover at {url}");
+                Self::Remote(RemoteProver::new(
+                    url.clone(),
+                    config.remote_prover_token.clone(),
+                    load_verifying_key(
+                        config.stake_table_capacity,
+                        config.key_cache_dir.as_deref(),
+                    ),
+                ))
+            }
+            None => {
+                let (pk, vk) =
+                    load_key_pair(config.stake_table_capacity, config.key_cache_dir.as_deref());
+                Self::Local(pk, vk)
+            }
+        }
+    }
+
+    /// The verifying key for proofs generated by this backend.
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        match self {
+            Self::Local(_, vk) => vk,
+            Self::Remote(prover) => prover.verifying_key(),
+            #[cfg(test)]
+            Self::Mock(prover) => &prover.vk,
+        }
+    }
+
+    /// Generate a proof for a light client state update.
+    ///
+    /// Local proving is computationally heavy and blocks the calling thread until it completes.
+    pub async fn prove(&self, request: &ProofRequest) -> Result<(Proof, PublicInput), ProverError> {
+        let height = request.state.block_height;
+        let proof_gen_start = Instant::now();
+        let res = match self {
+            Self::Local(proving_key, _) => generate_state_update_proof::<_, _, _, _>(
+                &mut ark_std::rand::thread_rng(),
+                proving_key,
+                &request.stake_table,
+                &request.signer_bit_vec,
+                &request.signatures,
+                &request.state,
+                &request.threshold,
+                request.stake_table_capacity,
+            )?,
+            Self::Remote(prover) => prover.prove(request).await?,
+            #[cfg(test)]
+            Self::Mock(prover) => prover.prove(request).await?,
+        };
+        let proof_gen_elapsed = Instant::now().signed_duration_since(proof_gen_start);
+        tracing::info!(
+            height,
+            "Proof generation completed. Elapsed: {proof_gen_elapsed:.3}"
+        );
+        Ok(res)
+    }
 }
 
 pub fn init_stake_table(
@@ -173,19 +349,47 @@ pub async fn light_client_genesis(
     Ok(pi.into())
 }
 
-pub fn load_proving_key(stake_table_capacity: usize) -> ProvingKey {
-    let srs = {
-        let num_gates = crate::circuit::build_for_preprocessing::<
-            CircuitField,
-            ark_ed_on_bn254::EdwardsConfig,
-        >(stake_table_capacity)
-        .unwrap()
-        .0
-        .num_gates();
+/// Load the proving and verifying keys, from the key cache in `cache_dir` if given.
+pub fn load_key_pair(
+    stake_table_capacity: usize,
+    cache_dir: Option<&Path>,
+) -> (ProvingKey, VerifyingKey) {
+    match cache_dir {
+        Some(dir) => {
+            let cache = KeyCache::new(dir, stake_table_capacity);
+            (cache.proving_key(), cache.verifying_key())
+        }
+        None => load_keys(stake_table_capacity),
+    }
+}
+
+/// Load the verifying key, used to check proofs generated by an external proving service.
+pub fn load_verifying_key(stake_table_capacity: usize, cache_dir: Option<&Path>) -> VerifyingKey {
+    match cache_dir {
+        Some(dir) => KeyCache::new(dir, stake_table_capacity).verifying_key(),
+        None => load_keys(stake_table_capacity).1,
+    }
+}
+
+/// Degree of the SRS needed for the state update circuit with the given stake table capacity.
+pub fn srs_degree(stake_table_capacity: usize) -> usize {
+    let num_gates = crate::circuit::build_for_preprocessing::<
+        CircuitField,
+        ark_ed_on_bn254::EdwardsConfig,
+    >(stake_table_capacity)
+    .unwrap()
+    .0
+    .num_gates();
+    num_gates + 2
+}
 
+/// Load the SRS and derive the proving and verifying keys for the state update circuit.
+pub fn load_keys(stake_table_capacity: usize) -> (ProvingKey, VerifyingKey) {
+    let srs = {
         std::println!("Loading SRS from Aztec's ceremony...");
         let srs_timer = Instant::now();
-        let srs = ark_srs::kzg10::aztec20::setup(num_gates + 2).expect("Aztec SRS fail to load");
+        let srs = ark_srs::kzg10::aztec20::setup(srs_degree(stake_table_capacity))
+            .expect("Aztec SRS fail to load");
         let srs_elapsed = Instant::now().signed_duration_since(srs_timer);
         std::println!("Done in {srs_elapsed:.3}");
 
@@ -201,11 +405,11 @@ pub fn load_proving_key(stake_table_capacity: usize) -> ProvingKey {
 
     std::println!("Generating proving key and verification key.");
     let key_gen_timer = Instant::now();
-    let (pk, _) = crate::snark::preprocess(&srs, stake_table_capacity)
+    let keys = crate::snark::preprocess(&srs, stake_table_capacity)
         .expect("Fail to preprocess state prover circuit");
     let key_gen_elapsed = Instant::now().signed_duration_since(key_gen_timer);
     std::println!("Done in {key_gen_elapsed:.3}");
-    pk
+    keys
 }
 
 pub async fn fetch_latest_state<Ver: StaticVersionType>(
@@ -218,13 +422,30 @@ pub async fn fetch_latest_state<Ver: StaticVersionType>(
         .await
 }
 
+/// Fetch the state signatures bundle at `height` from the relay server.
+pub async fn fetch_state_at<Ver: StaticVersionType>(
+    client: &Client<ServerError, Ver>,
+    height: u64,
+) -> Result<StateSignaturesBundle, ServerError> {
+    tracing::info!(
+        height,
+        "Fetching state signatures bundle from relay server."
+    );
+    client
+        .get::<StateSignaturesBundle>(&format!("/api/state/{height}"))
+        .send()
+        .await
+}
+
 /// prepare a contract interface ready to be read from or written to
-async fn prepare_contract(
+pub(crate) async fn prepare_contract(
     config: &StateProverConfig,
 ) -> Result<LightClient<L1Wallet>, ProverError> {
     let provider = Provider::try_from(config.l1_provider.to_string())
         .expect("unable to instantiate Provider, likely wrong URL");
-    let signer = Wallet::from(config.eth_signing_key.clone())
+    let signer = config
+        .signer
+        .clone()
         .with_chain_id(provider.get_chainid().await?.as_u64());
     let l1_wallet = Arc::new(L1Wallet::new(provider, signer));
 
@@ -249,54 +470,129 @@ pub async fn read_contract_state(
     Ok(state)
 }
 
+/// Versions of the LightClient contract which this prover can update.
+///
+/// Each major version of the contract may change the public input of the state update circuit and
+/// the function used to submit updates, so the prover must know which version it is talking to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LightClientVersion {
+    /// Updates are submitted with `newFinalizedState(LightClientState, PlonkProof)`.
+    V1,
+}
+
+impl LightClientVersion {
+    /// Detect the version of the LightClient contract at `config.light_client_address`.
+    ///
+    /// Fails with [`ProverError::UnsupportedContractVersion`] if the contract has been upgraded to
+    /// a major version this prover does not support, rather than submitting proofs it will reject.
+    pub async fn detect(config: &StateProverConfig) -> Result<Self, ProverError> {
+        let contract = prepare_contract(config).await?;
+        let (major, minor, patch) = contract
+            .get_version()
+            .call()
+            .await
+            .map_err(|err| ProverError::ContractError(err.into()))?;
+        match major {
+            1 => Ok(Self::V1),
+            _ => Err(ProverError::UnsupportedContractVersion(format!(
+                "{major}.{minor}.{patch}"
+            ))),
+        }
+    }
+}
+
 /// submit the latest finalized state along with a proof to the L1 LightClient contract
+///
+/// If the update is not mined in time it is resubmitted according to `config.resubmission`.
 pub async fn submit_state_and_proof(
     proof: Proof,
     public_input: PublicInput,
     config: &StateProverConfig,
-) -> Result<(), ProverError> {
+) -> Result<TransactionReceipt, ProverError> {
     let contract = prepare_contract(config).await?;
 
     // prepare the input the contract call and the tx itself
     let proof: ParsedPlonkProof = proof.into();
     let new_state: ParsedLightClientState = public_input.into();
+    let height = new_state.block_height as usize;
     let tx = contract.new_finalized_state(new_state.into(), proof.into());
 
     // send the tx
-    let (receipt, included_block) = sequencer_utils::contract_send::<_, _, LightClientErrors>(&tx)
-        .await
-        .map_err(ProverError::ContractError)?;
+    let receipt = send_update(&contract, tx, height, config).await?;
 
     tracing::info!(
-        "Submitted state and proof to L1: tx={:x} block={included_block}",
+        "Submitted state and proof to L1: tx={:x} block={:?}",
         receipt.transaction_hash,
+        receipt.block_number,
     );
 
-    Ok(())
+    Ok(receipt)
 }
 
-pub async fn sync_state<Ver: StaticVersionType>(
+/// Fetch the next state to prove from the relay server and collect the witness for proving it.
+///
+/// The next state is normally the latest signed state, unless that is past the end of the current
+/// epoch, in which case it is the state at the end of the epoch (see [`crate::schedule`]).
+///
+/// Returns `None` if the next state is not newer than both the state on the contract and `after`,
+/// the block height of the last state already handed to a prover.
+pub async fn fetch_witness<Ver: StaticVersionType>(
     st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>,
-    proving_key: &ProvingKey,
     relay_server_client: &Client<ServerError, Ver>,
     config: &StateProverConfig,
-) -> Result<(), ProverError> {
-    tracing::info!("Start syncing light client state.");
-
-    let bundle = fetch_latest_state(relay_server_client).await?;
-    tracing::info!("Latest HotShot block height: {}", bundle.state.block_height);
-    let old_state = read_contract_state(config).await?;
+    metrics: &ProverMetrics,
+    after: usize,
+) -> Result<Option<ScheduledUpdate>, ProverError> {
+    let latest = fetch_latest_state(relay_server_client).await?;
+    let latest_height = latest.state.block_height;
+    tracing::info!("Latest HotShot block height: {latest_height}");
+    let epoch = EpochInfo::fetch(config).await?;
     tracing::info!(
         "Current HotShot block height on contract: {}",
-        old_state.block_height
+        epoch.finalized_height
     );
-    if old_state.block_height >= bundle.state.block_height {
-        tracing::info!("No update needed.");
-        return Ok(());
+    metrics.update_heights(latest_height, epoch.finalized_height as usize);
+    let epoch = if config.skip_ahead {
+        epoch.ahead_of(after as u64)
+    } else {
+        epoch
+    };
+    let (bundle, epoch_end) = match epoch.schedule(latest_height as u64) {
+        Target::UpToDate => {
+            tracing::info!("No update needed.");
+            return Ok(None);
+        }
+        Target::Latest => (latest, false),
+        Target::EpochEnd(height) => {
+            tracing::info!(height, "Updating to the last block of the epoch first.");
+            if height == latest_height as u64 {
+                (latest, true)
+            } else {
+                (fetch_state_at(relay_server_client, height).await?, true)
+            }
+        }
+    };
+    if bundle.state.block_height <= after {
+        tracing::info!("State is already being proven.");
+        return Ok(None);
     }
-    tracing::debug!("Old state: {old_state:?}");
     tracing::debug!("New state: {:?}", bundle.state);
 
+    Ok(Some(ScheduledUpdate {
+        request: collect_witness(st, bundle, config.stake_table_capacity)?,
+        epoch: epoch.verifying_epoch(),
+        epoch_end,
+    }))
+}
+
+/// Assemble the witness for proving the state in `bundle`, keeping only valid signatures.
+///
+/// Fails if the signers with valid signatures do not hold enough stake to reach the threshold.
+pub fn collect_witness(
+    st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>,
+    bundle: StateSignaturesBundle,
+    stake_table_capacity: usize,
+) -> Result<ProofRequest, ProverError> {
     let threshold = st.total_stake(SnapshotVersion::LastEpochStart)? * 2 / 3;
     tracing::info!("Threshold before syncing state: {}", threshold);
     let entries = st
@@ -331,42 +627,157 @@ pub async fn sync_state<Ver: StaticVersionType>(
     //     st.commitment(SnapshotVersion::LastEpochStart).unwrap()
     // );
 
-    tracing::info!("Collected latest state and signatures. Start generating SNARK proof.");
-    let proof_gen_start = Instant::now();
-    let (proof, public_input) = generate_state_update_proof::<_, _, _, _>(
-        &mut ark_std::rand::thread_rng(),
-        proving_key,
-        &entries,
+    Ok(ProofRequest {
+        stake_table: entries,
         signer_bit_vec,
         signatures,
-        &bundle.state,
-        &threshold,
-        config.stake_table_capacity,
-    )?;
-    let proof_gen_elapsed = Instant::now().signed_duration_since(proof_gen_start);
-    tracing::info!("Proof generation completed. Elapsed: {proof_gen_elapsed:.3}");
+        state: bundle.state,
+        threshold,
+        stake_table_capacity,
+    })
+}
+
+/// Submit a proven state to whichever version of the LightClient contract is deployed.
+async fn submit_proof(
+    proof: Proof,
+    public_input: PublicInput,
+    config: &StateProverConfig,
+    metrics: &ProverMetrics,
+) -> Result<TransactionReceipt, ProverError> {
+    // The contract may have been upgraded since the last update.
+    let version = LightClientVersion::detect(config).await?;
+    tracing::info!("Light client contract version: {version:?}");
+    metrics.submissions.add(1);
+    let res = match version {
+        LightClientVersion::V1 => submit_state_and_proof(proof, public_input, config).await,
+    };
+    match res {
+        Ok(receipt) => {
+            if let Some(gas_used) = receipt.gas_used {
+                metrics.gas_used.add(gas_used.as_usize());
+            }
+            Ok(receipt)
+        }
+        // Another prover got there first; this is not a failure of ours.
+        Err(err @ ProverError::Superseded(_)) => Err(err),
+        Err(err) => {
+            metrics.submission_failures.add(1);
+            Err(err)
+        }
+    }
+}
 
-    submit_state_and_proof(proof, public_input, config).await?;
+/// Generate a proof for `request`, unless one is already cached.
+pub async fn prove_with_cache<Ver: StaticVersionType>(
+    backend: &ProvingBackend<Ver>,
+    cache: &ProofCache,
+    metrics: &ProverMetrics,
+    request: ProofRequest,
+) -> Result<(Proof, PublicInput), ProverError> {
+    let height = request.state.block_height;
+    if let Some(cached) = cache.get(&request) {
+        tracing::info!(height, "Reusing cached proof.");
+        return Ok(cached);
+    }
+    let start = Instant::now();
+    let (proof, public_input) = backend.prove(&request).await?;
+    metrics
+        .proof_generation_duration
+        .add_point(start.elapsed().as_secs_f64());
+    cache.insert(&request, &proof);
+    Ok((proof, public_input))
+}
+
+pub async fn sync_state<Ver: StaticVersionType>(
+    st: &StakeTable<BLSPubKey, StateVerKey, CircuitField>,
+    backend: &ProvingBackend<Ver>,
+    cache: &ProofCache,
+    relay_server_client: &Client<ServerError, Ver>,
+    config: &StateProverConfig,
+    metrics: &ProverMetrics,
+) -> Result<(), ProverError> {
+    tracing::info!("Start syncing light client state.");
+
+    let Some(update) = fetch_witness(st, relay_server_client, config, metrics, 0).await? else {
+        return Ok(());
+    };
+
+    tracing::info!("Collected latest state and signatures. Start generating SNARK proof.");
+    let (proof, public_input) = prove_with_cache(backend, cache, metrics, update.request).await?;
+    submit_proof(proof.clone(), public_input.clone(), config, metrics).await?;
+    for mirror in &config.mirrors {
+        if let Err(err) = submit_proof(
+            proof.clone(),
+            public_input.clone(),
+            &config.for_target(mirror),
+            metrics,
+        )
+        .await
+        {
+            tracing::error!(?mirror, "Cannot submit the light client state: {err}");
+        }
+    }
 
     tracing::info!("Successfully synced light client state.");
     Ok(())
 }
 
+/// State of the prover's HTTP server.
+struct ServerState {
+    registry: PrometheusMetrics,
+    metrics: Arc<ProverMetrics>,
+    update_interval: Duration,
+}
+
 fn start_http_server<Ver: StaticVersionType + 'static>(
     port: u16,
     lightclient_address: Address,
+    registry: PrometheusMetrics,
+    metrics: Arc<ProverMetrics>,
+    update_interval: Duration,
     bind_version: Ver,
 ) -> io::Result<()> {
-    let mut app = tide_disco::App::<(), ServerError>::with_state(());
+    let mut app =
+        tide_disco::App::<RwLock<ServerState>, ServerError>::with_state(RwLock::new(ServerState {
+            registry,
+            metrics,
+            update_interval,
+        }));
     let toml = toml::from_str::<toml::value::Value>(include_str!("../api/prover-service.toml"))
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
-    let mut api = Api::<(), ServerError, Ver>::new(toml)
+    let mut api = Api::<RwLock<ServerState>, ServerError, Ver>::new(toml)
         .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     api.get("getlightclientcontract", move |_, _| {
         async move { Ok(lightclient_address) }.boxed()
     })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("status", |_, state| {
+        async move { Ok(state.metrics.status()) }.boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .get("health", |_, state| {
+        async move {
+            let status = state.metrics.status();
+            if status.is_healthy(state.update_interval) {
+                Ok(status.srs)
+            } else {
+                Err(ServerError::catch_all(
+                    StatusCode::ServiceUnavailable,
+                    format!(
+                        "prover has not checked for a new state since {:?}",
+                        status.last_checked
+                    ),
+                ))
+            }
+        }
+        .boxed()
+    })
+    .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?
+    .metrics("metrics", |_, state| {
+        async move { Ok(Cow::Borrowed(&state.registry)) }.boxed()
+    })
     .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
 
     app.register_module("api", api)
@@ -390,45 +801,520 @@ pub async fn run_prover_service<Ver: StaticVersionType + 'static>(
     let relay_server_client =
         Arc::new(Client::<ServerError, Ver>::new(config.relay_server.clone()));
 
+    let registry = PrometheusMetrics::default();
+    let metrics = Arc::new(ProverMetrics::new(&registry));
+
     // Start the HTTP server to get a functioning healthcheck before any heavy computations.
     if let Some(port) = config.port {
-        if let Err(err) = start_http_server(port, config.light_client_address, bind_version) {
+        if let Err(err) = start_http_server(
+            port,
+            config.light_client_address,
+            registry,
+            metrics.clone(),
+            config.update_interval,
+            bind_version,
+        ) {
             tracing::error!("Error starting http server: {}", err);
         }
     }
 
-    let proving_key = async_std::task::block_on(async move {
-        Arc::new(load_proving_key(config.stake_table_capacity))
+    // Fail fast if the contract is already too new for us, before loading the proving key.
+    match LightClientVersion::detect(&config).await {
+        Ok(version) => tracing::info!("Light client contract version: {version:?}"),
+        Err(err @ ProverError::UnsupportedContractVersion(_)) => panic!("{err}"),
+        Err(err) => tracing::error!("Cannot detect the light client contract version: {err}"),
+    }
+
+    let backend = block_on({
+        let config = config.clone();
+        async move { Arc::new(ProvingBackend::<Ver>::init(&config)) }
+    });
+    metrics.update_status(|status| status.srs = SrsStatus::Loaded);
+
+    // Pipeline successive updates: while one state is being proven or submitted, the witness for
+    // the next one is already being collected, and up to `proving_workers` proofs are generated
+    // concurrently. At most one witness waits for a free worker; newer ones are dropped rather than
+    // queued when all workers are busy, since a newer state will be available by the next update.
+    // The exception is the update for the last block of an epoch, which the contract requires
+    // before any later state: it replaces any waiting witness and is never dropped.
+    let workers = config.proving_workers.max(1);
+    let (witness_send, witness_recv) = channel::bounded::<ScheduledUpdate>(1);
+    let (proof_send, proof_recv) = channel::unbounded();
+    let cache = Arc::new(ProofCache::new(
+        config.proof_cache_capacity,
+        config.proof_cache_dir.clone(),
+        backend.verifying_key(),
+    ));
+    // Block height of the last state handed to a prover. If proving or submitting it fails, this
+    // is rolled back so that the state can be dispatched again.
+    let last_dispatched = Arc::new(AtomicUsize::new(0));
+    let checkpoint = config
+        .checkpoint_path
+        .as_ref()
+        .map(|path| Arc::new(Checkpoint::new(path)));
+    for i in 0..workers {
+        spawn(proving_worker(
+            i,
+            backend.clone(),
+            cache.clone(),
+            metrics.clone(),
+            last_dispatched.clone(),
+            witness_recv.clone(),
+            proof_send.clone(),
+        ));
+    }
+    // Every proof is submitted to each target contract by a separate task, so that a slow or failing
+    // chain does not hold up updates to the others.
+    let mut target_senders = vec![];
+    for (i, target) in config.targets().enumerate() {
+        let (send, recv) = channel::unbounded();
+        spawn(submission_task(
+            config.for_target(&target),
+            metrics.clone(),
+            (i == 0).then(|| last_dispatched.clone()),
+            checkpoint.clone().filter(|_| i == 0),
+            recv,
+        ));
+        target_senders.push(send);
+    }
+    spawn(async move {
+        while let Ok(update) = proof_recv.recv().await {
+            for sender in &target_senders {
+                sender.send(update.clone()).await.ok();
+            }
+        }
     });
 
+    if let Some(checkpoint) = &checkpoint {
+        resume_from_checkpoint(&config, checkpoint, &last_dispatched, witness_send.clone()).await;
+        spawn(checkpoint_on_shutdown(checkpoint.clone()));
+    }
+
     let update_interval = config.update_interval;
+    let mut coordinator = Coordinator::new(&config);
+    // Set while the end of an epoch waits for a free proving worker. No later state is scheduled
+    // until it has been handed over, so that it is proven first.
+    let handoff = Arc::new(AtomicBool::new(false));
     loop {
-        let st = st.clone();
-        let proving_key = proving_key.clone();
-        let relay_server_client = relay_server_client.clone();
-        let config = config.clone();
-        // Use block_on to avoid blocking the async runtime with this computationally heavy task
-        async_std::task::block_on(async move {
-            if let Err(err) = sync_state(&st, &proving_key, &relay_server_client, &config).await {
-                tracing::error!("Cannot sync the light client state: {}", err);
+        // Fail fast if the contract has been upgraded to a version we cannot update.
+        if let Err(err @ ProverError::UnsupportedContractVersion(_)) =
+            LightClientVersion::detect(&config).await
+        {
+            panic!("{err}");
+        }
+        let res = match coordinator.should_update(&config).await {
+            Ok(true) if handoff.load(Ordering::SeqCst) => {
+                tracing::info!("Waiting for a proving worker to take the end of the epoch.");
+                Ok(None)
+            }
+            Ok(true) => {
+                fetch_witness(
+                    &st,
+                    &relay_server_client,
+                    &config,
+                    &metrics,
+                    last_dispatched.load(Ordering::SeqCst),
+                )
+                .await
+            }
+            Ok(false) => Ok(None),
+            Err(err) => Err(err),
+        };
+        let mut catching_up = false;
+        match res {
+            Ok(Some(update)) => {
+                let height = update.request.state.block_height;
+                let epoch_end = update.epoch_end;
+                let scheduled = checkpoint.as_ref().map(|_| update.clone());
+                match dispatch(update, &witness_send, &witness_recv, &handoff) {
+                    Ok(()) => {
+                        tracing::info!(height, "Dispatched light client state for proving.");
+                        last_dispatched.store(height, Ordering::SeqCst);
+                        catching_up = config.skip_ahead && epoch_end;
+                        if let (Some(checkpoint), Some(update)) = (&checkpoint, &scheduled) {
+                            checkpoint.dispatched(update);
+                        }
+                    }
+                    Err(_) => {
+                        tracing::warn!(height, "All proving workers are busy, skipping state.")
+                    }
+                }
+                metrics.queue_depth.set(witness_send.len());
             }
-        });
+            Ok(None) => {}
+            Err(err) => tracing::error!("Cannot sync the light client state: {}", err),
+        }
+        metrics.update_status(ProverStatus::checked);
+        if catching_up {
+            // More epochs may have ended since the one just dispatched. Schedule the next update
+            // right away rather than after the update interval.
+            continue;
+        }
         tracing::info!("Sleeping for {:?}", update_interval);
         sleep(update_interval).await;
     }
 }
 
+/// Hand a scheduled update to the proving workers.
+///
+/// A routine update is dropped if a witness is already waiting for a free worker. The end of an
+/// epoch is never dropped: it replaces a waiting witness for an earlier state, which it supersedes,
+/// unless that is itself the end of an earlier epoch in skip-ahead mode. Since the workers may all
+/// be busy for minutes, it is handed over in the background so as not to hold up the main loop, and
+/// `handoff` is set until it has been.
+fn dispatch(
+    update: ScheduledUpdate,
+    witnesses: &Sender<ScheduledUpdate>,
+    waiting: &Receiver<ScheduledUpdate>,
+    handoff: &Arc<AtomicBool>,
+) -> Result<(), TrySendError<ScheduledUpdate>> {
+    if !update.epoch_end {
+        return witnesses.try_send(update);
+    }
+    let requeue = waiting.try_recv().ok().filter(|waiting| waiting.epoch_end);
+    handoff.store(true, Ordering::SeqCst);
+    let witnesses = witnesses.clone();
+    let handoff = handoff.clone();
+    spawn(async move {
+        for update in requeue.into_iter().chain([update]) {
+            if witnesses.send(update).await.is_err() {
+                break;
+            }
+        }
+        handoff.store(false, Ordering::SeqCst);
+    });
+    Ok(())
+}
+
+/// Dispatch the updates checkpointed by a previous run which the contract still needs.
+///
+/// The updates are handed to the proving workers in order of height, ahead of any newly scheduled
+/// update, and `last_dispatched` is advanced past them so they are not scheduled again.
+async fn resume_from_checkpoint(
+    config: &StateProverConfig,
+    checkpoint: &Checkpoint,
+    last_dispatched: &AtomicUsize,
+    witnesses: Sender<ScheduledUpdate>,
+) {
+    let updates = checkpoint.load();
+    if updates.is_empty() {
+        return;
+    }
+    let info = match EpochInfo::fetch(config).await {
+        Ok(info) => info,
+        Err(err) => {
+            tracing::error!("Cannot read the light client state, not resuming checkpoint: {err}");
+            return;
+        }
+    };
+    let updates = still_needed(updates, &info);
+    let Some(last) = updates.last() else {
+        tracing::info!("Light client is past all checkpointed updates.");
+        return;
+    };
+    let height = last.request.state.block_height;
+    tracing::info!(
+        count = updates.len(),
+        height,
+        "Resuming light client updates from checkpoint."
+    );
+    for update in &updates {
+        checkpoint.dispatched(update);
+    }
+    last_dispatched.store(height, Ordering::SeqCst);
+    // Only one witness waits for a free worker, so hand the rest over in the background rather than
+    // holding up the main loop until they are all being proven.
+    spawn(async move {
+        for update in updates {
+            if witnesses.send(update).await.is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// Write the checkpoint and exit once the service is asked to shut down.
+async fn checkpoint_on_shutdown(checkpoint: Arc<Checkpoint>) {
+    let mut signals = match Signals::new([SIGINT, SIGTERM]) {
+        Ok(signals) => signals,
+        Err(err) => {
+            tracing::error!(
+                "Cannot install shutdown handler, updates will not be checkpointed: {err}"
+            );
+            return;
+        }
+    };
+    if let Some(signal) = signals.next().await {
+        tracing::info!(signal, "Shutting down.");
+        if let Err(err) = checkpoint.save() {
+            tracing::error!(
+                "Cannot write checkpoint {}: {err}",
+                checkpoint.path().display()
+            );
+        }
+        std::process::exit(0);
+    }
+}
+
+/// Generate proofs for witnesses received from the main loop.
+async fn proving_worker<Ver: StaticVersionType + 'static>(
+    id: usize,
+    backend: Arc<ProvingBackend<Ver>>,
+    cache: Arc<ProofCache>,
+    metrics: Arc<ProverMetrics>,
+    last_dispatched: Arc<AtomicUsize>,
+    witnesses: Receiver<ScheduledUpdate>,
+    proofs: Sender<ProvenUpdate>,
+) {
+    while let Ok(ScheduledUpdate {
+        request,
+        epoch,
+        epoch_end,
+    }) = witnesses.recv().await
+    {
+        let height = request.state.block_height;
+        tracing::info!(id, height, "Start generating SNARK proof.");
+        metrics.queue_depth.set(witnesses.len());
+        let backend = backend.clone();
+        let cache = cache.clone();
+        let worker_metrics = metrics.clone();
+        // Prove on a dedicated thread to avoid blocking the async runtime with this computationally
+        // heavy task.
+        let res = spawn_blocking(move || {
+            block_on(prove_with_cache(&backend, &cache, &worker_metrics, request))
+        })
+        .await;
+        match res {
+            Ok((proof, public_input)) => {
+                metrics.update_status(|status| {
+                    status.last_proven_height = status.last_proven_height.max(Some(height));
+                });
+                let proven = ProvenUpdate {
+                    height,
+                    epoch,
+                    epoch_end,
+                    proof,
+                    public_input,
+                };
+                if proofs.send(proven).await.is_err() {
+                    break;
+                }
+            }
+            Err(err) => {
+                tracing::error!(id, height, "Cannot generate proof: {}", err);
+                last_dispatched.fetch_min(height.saturating_sub(1), Ordering::SeqCst);
+            }
+        }
+    }
+    tracing::warn!(id, "proving worker exiting");
+}
+
+/// A generated proof waiting to be submitted.
+#[derive(Clone)]
+struct ProvenUpdate {
+    height: usize,
+    /// The epoch in which the update was scheduled.
+    epoch: u64,
+    /// Whether this is the update for the last block of the epoch.
+    epoch_end: bool,
+    proof: Proof,
+    public_input: PublicInput,
+}
+
+/// Submit proofs to one light client contract as they are generated.
+///
+/// Proofs may be completed out of order when there are several workers. A proof for a state older
+/// than one already submitted would be rejected by the contract, so it is dropped instead. So is a
+/// proof scheduled before the contract moved to a new epoch. A proof scheduled for a later epoch,
+/// as happens in skip-ahead mode, is held until the contract reaches that epoch.
+///
+/// The primary contract drives scheduling, so its task is given `last_dispatched`: when an update
+/// to the primary contract fails, the state is scheduled again from a fresh witness. Mirror
+/// contracts are not consulted when scheduling, so their tasks retry a failed proof themselves after
+/// the update interval, unless a newer proof arrives first. A failed update for the last block of
+/// an epoch is always retried, since the contract accepts no later state until it lands. The
+/// primary task is also given the checkpoint, if any, to forget updates once they land.
+async fn submission_task(
+    config: StateProverConfig,
+    metrics: Arc<ProverMetrics>,
+    last_dispatched: Option<Arc<AtomicUsize>>,
+    checkpoint: Option<Arc<Checkpoint>>,
+    proofs: Receiver<ProvenUpdate>,
+) {
+    let contract = config.light_client_address;
+    let mut last_submitted = 0;
+    let mut retry: Option<(ProvenUpdate, Instant)> = None;
+    // Proofs waiting for the contract to reach their epoch, and those to try again now that the
+    // contract has advanced.
+    let mut deferred = BTreeMap::<usize, ProvenUpdate>::new();
+    let mut ready = VecDeque::new();
+    loop {
+        let update = if let Some(update) = ready.pop_front() {
+            update
+        } else {
+            match retry.take() {
+                None => match proofs.recv().await {
+                    Ok(update) => update,
+                    Err(_) => break,
+                },
+                Some((failed, at)) => {
+                    match timeout(at.saturating_duration_since(Instant::now()), proofs.recv()).await
+                    {
+                        Ok(Ok(update)) if !failed.epoch_end => update,
+                        // Later states are only valid once the end of the epoch lands.
+                        Ok(Ok(update)) => {
+                            defer(&mut deferred, update, contract, last_dispatched.as_deref());
+                            retry = Some((failed, at));
+                            continue;
+                        }
+                        Ok(Err(_)) => break,
+                        Err(_) => failed,
+                    }
+                }
+            }
+        };
+        let height = update.height;
+        if height <= last_submitted {
+            tracing::info!(
+                height,
+                last_submitted,
+                ?contract,
+                "Dropping outdated proof."
+            );
+            continue;
+        }
+        match EpochInfo::fetch(&config).await {
+            Ok(info) if info.verifying_epoch() < update.epoch => {
+                tracing::info!(
+                    height,
+                    ?contract,
+                    epoch = update.epoch,
+                    current_epoch = info.verifying_epoch(),
+                    "Holding proof until the contract reaches its epoch."
+                );
+                defer(&mut deferred, update, contract, last_dispatched.as_deref());
+                continue;
+            }
+            Ok(info) if info.verifying_epoch() > update.epoch => {
+                tracing::warn!(
+                    height,
+                    ?contract,
+                    epoch = update.epoch,
+                    current_epoch = info.verifying_epoch(),
+                    "Epoch changed since the state was scheduled, dropping proof."
+                );
+                if let Some(last_dispatched) = &last_dispatched {
+                    last_dispatched.fetch_min(height.saturating_sub(1), Ordering::SeqCst);
+                }
+                continue;
+            }
+            Ok(_) => {}
+            // The contract rejects the update if it is no longer valid, so submit it anyway.
+            Err(err) => tracing::warn!(
+                height,
+                ?contract,
+                "Cannot check the light client epoch: {err}"
+            ),
+        }
+        let res = submit_proof(
+            update.proof.clone(),
+            update.public_input.clone(),
+            &config,
+            &metrics,
+        )
+        .await;
+        match res {
+            Ok(receipt) => {
+                tracing::info!(height, ?contract, "Successfully synced light client state.");
+                last_submitted = height;
+                ready.extend(mem::take(&mut deferred).into_values());
+                if let Some(checkpoint) = &checkpoint {
+                    checkpoint.finalized(height);
+                }
+                if last_dispatched.is_some() {
+                    metrics.update_status(|status| {
+                        status.last_submitted_height = Some(height);
+                        status.last_submitted_tx = Some(receipt.transaction_hash);
+                    });
+                }
+            }
+            Err(err @ ProverError::Superseded(landed)) => {
+                tracing::info!(height, ?contract, "Abandoning light client update: {err}");
+                last_submitted = height;
+                ready.extend(mem::take(&mut deferred).into_values());
+                if let Some(checkpoint) = &checkpoint {
+                    checkpoint.finalized(landed);
+                }
+            }
+            Err(err) => {
+                tracing::error!(
+                    height,
+                    ?contract,
+                    "Cannot submit the light client state: {}",
+                    err
+                );
+                // The contract has not advanced, so proofs released by the previous update are
+                // still waiting for it.
+                deferred.extend(ready.drain(..).map(|update| (update.height, update)));
+                match &last_dispatched {
+                    Some(last_dispatched) => {
+                        last_dispatched.fetch_min(height.saturating_sub(1), Ordering::SeqCst);
+                    }
+                    None => retry = Some((update, Instant::now() + config.update_interval)),
+                }
+            }
+        }
+    }
+    tracing::warn!(?contract, "proof submission task exiting");
+}
+
+/// Hold `update` until the contract it is for advances, dropping the latest held proofs if too many
+/// are waiting.
+fn defer(
+    deferred: &mut BTreeMap<usize, ProvenUpdate>,
+    update: ProvenUpdate,
+    contract: Address,
+    last_dispatched: Option<&AtomicUsize>,
+) {
+    deferred.insert(update.height, update);
+    while deferred.len() > MAX_DEFERRED_PROOFS {
+        let Some((height, _)) = deferred.pop_last() else {
+            break;
+        };
+        tracing::warn!(
+            height,
+            ?contract,
+            "Too many proofs waiting, dropping proof."
+        );
+        if let Some(last_dispatched) = last_dispatched {
+            last_dispatched.fetch_min(height.saturating_sub(1), Ordering::SeqCst);
+        }
+    }
+}
+
 /// Run light client state prover once
 pub async fn run_prover_once<Ver: StaticVersionType>(config: StateProverConfig, _: Ver) {
     let st =
         init_stake_table_from_orchestrator(&config.orchestrator_url, config.stake_table_capacity)
             .await;
-    let proving_key = load_proving_key(config.stake_table_capacity);
+    let backend = ProvingBackend::<Ver>::init(&config);
+    let cache = ProofCache::new(
+        config.proof_cache_capacity,
+        config.proof_cache_dir.clone(),
+        backend.verifying_key(),
+    );
     let relay_server_client = Client::<ServerError, Ver>::new(config.relay_server.clone());
 
-    sync_state(&st, &proving_key, &relay_server_client, &config)
-        .await
-        .expect("Error syncing the light client state.");
+    sync_state(
+        &st,
+        &backend,
+        &cache,
+        &relay_server_client,
+        &config,
+        &Default::default(),
+    )
+    .await
+    .expect("Error syncing the light client state.");
 }
 
 #[derive(Debug, Display)]
@@ -439,6 +1325,14 @@ pub enum ProverError {
     ContractError(anyhow::Error),
     /// Error when communicating with the state relay server: {0}
     RelayServerError(ServerError),
+    /// Error when communicating with the remote prover: {0}
+    RemoteProverError(ServerError),
+    /// The remote prover returned an invalid proof: {0}
+    InvalidRemoteProof(String),
+    /// Another light client update to block {0} landed first
+    Superseded(usize),
+    /// LightClient contract version {0} is not supported by this prover, please upgrade the prover
+    UnsupportedContractVersion(String),
     /// Internal error with the stake table
     StakeTableError(StakeTableError),
     /// Internal error when generating the SNARK proof
@@ -477,12 +1371,16 @@ impl std::error::Error for ProverError {}
 mod test {
 
     use super::*;
-    use crate::mock_ledger::{MockLedger, MockSystemParam};
+    use crate::mock_ledger::{Adversary, MockLedger, MockSystemParam};
     use anyhow::Result;
     use ark_ed_on_bn254::EdwardsConfig;
     use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
+    use contract_bindings::light_client_mock::LightClientMock;
+    use es_version::SequencerVersion;
+    use ethers::signers::LocalWallet;
     use ethers::{
         abi::AbiEncode,
+        types::{TransactionRequest, H256},
         utils::{Anvil, AnvilInstance},
     };
     use hotshot_stake_table::vec_based::StakeTable;
@@ -490,6 +1388,7 @@ mod test {
     use jf_primitives::signatures::{SchnorrSignatureScheme, SignatureScheme};
     use jf_utils::test_rng;
     use sequencer_utils::deployer;
+    use std::{collections::HashMap, sync::Mutex};
 
     const STAKE_TABLE_CAPACITY_FOR_TEST: usize = 10;
     const BLOCKS_PER_EPOCH: u32 = 10;
@@ -531,6 +1430,25 @@ mod test {
         (genesis, qc_keys, state_keys, st)
     }
 
+    /// Proving and verifying keys for the test stake table capacity.
+    fn keys_for_test() -> (ProvingKey, VerifyingKey) {
+        let srs = {
+            // load SRS from Aztec's ceremony
+            let srs = ark_srs::kzg10::aztec20::setup(2u64.pow(16) as usize + 2)
+                .expect("Aztec SRS fail to load");
+            // convert to Jellyfish type
+            // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
+            UnivariateUniversalParams {
+                powers_of_g: srs.powers_of_g,
+                h: srs.h,
+                beta_h: srs.beta_h,
+                powers_of_h: vec![srs.h, srs.beta_h],
+            }
+        };
+        crate::preprocess(&srs, STAKE_TABLE_CAPACITY_FOR_TEST)
+            .expect("Fail to preprocess state prover circuit")
+    }
+
     // everybody signs, then generate a proof
     fn gen_state_proof(
         old_state: &ParsedLightClientState,
@@ -554,21 +1472,7 @@ mod test {
             .collect::<Result<Vec<_>, _>>()
             .unwrap();
 
-        let srs = {
-            // load SRS from Aztec's ceremony
-            let srs = ark_srs::kzg10::aztec20::setup(2u64.pow(16) as usize + 2)
-                .expect("Aztec SRS fail to load");
-            // convert to Jellyfish type
-            // TODO: (alex) use constructor instead https://github.com/EspressoSystems/jellyfish/issues/440
-            UnivariateUniversalParams {
-                powers_of_g: srs.powers_of_g,
-                h: srs.h,
-                beta_h: srs.beta_h,
-                powers_of_h: vec![srs.h, srs.beta_h],
-            }
-        };
-        let (pk, _) = crate::preprocess(&srs, STAKE_TABLE_CAPACITY_FOR_TEST)
-            .expect("Fail to preprocess state prover circuit");
+        let (pk, _) = keys_for_test();
         let stake_table_entries = st
             .try_iter(SnapshotVersion::LastEpochStart)
             .unwrap()
@@ -589,6 +1493,29 @@ mod test {
         (pi, proof)
     }
 
+    /// A proving backend which returns proofs generated ahead of time, after a delay per state.
+    pub struct MockProver {
+        pub(super) vk: VerifyingKey,
+        proofs: HashMap<usize, (Proof, PublicInput, Duration)>,
+        /// Heights of the states proven so far, in order of completion.
+        proven: Mutex<Vec<usize>>,
+    }
+
+    impl MockProver {
+        pub async fn prove(
+            &self,
+            request: &ProofRequest,
+        ) -> Result<(Proof, PublicInput), ProverError> {
+            let height = request.state.block_height;
+            let Some((proof, public_input, delay)) = self.proofs.get(&height).cloned() else {
+                return Err(ProverError::InvalidState(format!("no proof for {height}")));
+            };
+            sleep(delay).await;
+            self.proven.lock().unwrap().push(height);
+            Ok((proof, public_input))
+        }
+    }
+
     /// deploy LightClientMock.sol on local blockchain (via `anvil`) for testing
     /// return (signer-loaded wallet, contract instance)
     async fn deploy_contract_for_test(
@@ -596,17 +1523,15 @@ mod test {
         genesis: ParsedLightClientState,
     ) -> Result<(Arc<L1Wallet>, LightClient<L1Wallet>)> {
         let provider = Provider::<Http>::try_from(anvil.endpoint())?;
-        let signer = Wallet::from(anvil.keys()[0].clone())
+        let signer = LocalWallet::from(anvil.keys()[0].clone())
             .with_chain_id(provider.get_chainid().await?.as_u64());
-        let l1_wallet = Arc::new(L1Wallet::new(provider.clone(), signer));
+        let l1_wallet = Arc::new(L1Wallet::new(provider.clone(), signer.into()));
 
-        let mut contracts = deployer::Contracts::default();
-        let address = deployer::deploy_mock_light_client_contract(
-            l1_wallet.clone(),
-            &mut contracts,
-            Some((genesis.into(), BLOCKS_PER_EPOCH)),
-        )
-        .await?;
+        let address = deployer::Deployer::builder(l1_wallet.clone())
+            .build()
+            .await?
+            .deploy_mock_light_client(Some((genesis.into(), BLOCKS_PER_EPOCH)))
+            .await?;
 
         let proxy = LightClient::new(address, l1_wallet.clone());
 
@@ -618,7 +1543,7 @@ mod test {
         fn update_l1_info(&mut self, anvil: &AnvilInstance, light_client_address: Address) {
             self.l1_provider = Url::parse(&anvil.endpoint()).unwrap();
             self.light_client_address = light_client_address;
-            self.eth_signing_key = anvil.keys()[0].clone().into();
+            self.signer = LocalWallet::from(anvil.keys()[0].clone()).into();
         }
     }
     // only for testing purposes
@@ -629,10 +1554,22 @@ mod test {
                 update_interval: Duration::default(),
                 l1_provider: Url::parse("http://localhost").unwrap(),
                 light_client_address: Address::default(),
-                eth_signing_key: SigningKey::random(&mut test_rng()),
+                signer: LocalWallet::new(&mut test_rng()).into(),
                 orchestrator_url: Url::parse("http://localhost").unwrap(),
                 port: None,
                 stake_table_capacity: 10,
+                key_cache_dir: None,
+                remote_prover_url: None,
+                remote_prover_token: None,
+                proving_workers: 1,
+                skip_ahead: false,
+                proof_cache_capacity: 0,
+                proof_cache_dir: None,
+                checkpoint_path: None,
+                standby_priority: 0,
+                failover_timeout: Duration::default(),
+                resubmission: Default::default(),
+                mirrors: vec![],
             }
         }
     }
@@ -658,6 +1595,72 @@ mod test {
         Ok(())
     }
 
+    #[async_std::test]
+    async fn test_coordinator_standby() -> Result<()> {
+        setup_logging();
+        setup_backtrace();
+
+        let anvil = Anvil::new().spawn();
+        let (_wallet, contract) =
+            deploy_contract_for_test(&anvil, ParsedLightClientState::dummy_genesis()).await?;
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&anvil, contract.address());
+
+        // A primary replica always updates.
+        assert!(Coordinator::new(&config).should_update(&config).await?);
+
+        // A standby waits for the contract to stall before taking over.
+        config.standby_priority = 1;
+        config.failover_timeout = Duration::from_secs(3600);
+        assert!(!Coordinator::new(&config).should_update(&config).await?);
+        config.failover_timeout = Duration::ZERO;
+        assert!(Coordinator::new(&config).should_update(&config).await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_light_client_target_from_str() {
+        let target: LightClientTarget =
+            "0x0000000000000000000000000000000000000001@http://user@l2:8545"
+                .parse()
+                .unwrap();
+        assert_eq!(target.address, Address::from_low_u64_be(1));
+        assert_eq!(target.provider, Url::parse("http://user@l2:8545").unwrap());
+        assert!("http://l2:8545".parse::<LightClientTarget>().is_err());
+
+        let config = StateProverConfig {
+            mirrors: vec![target.clone()],
+            ..Default::default()
+        };
+        let targets = config.targets().collect::<Vec<_>>();
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].address, config.light_client_address);
+        assert_eq!(targets[1], target);
+        assert_eq!(
+            config.for_target(&target).light_client_address,
+            target.address
+        );
+    }
+
+    #[test]
+    fn test_collect_witness() {
+        let pp = MockSystemParam::init(BLOCKS_PER_EPOCH);
+        let mut ledger = MockLedger::init(pp, NUM_INIT_VALIDATORS as usize);
+        ledger.elapse_with_block();
+
+        let bundle = ledger.gen_signatures_bundle(None);
+        let witness = collect_witness(&ledger.st, bundle, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap();
+        assert!(witness.signer_bit_vec.iter().any(|signed| *signed));
+
+        // Invalid signatures are discarded, leaving too little stake to prove the state.
+        for adversary in [Adversary::InvalidSignatures, Adversary::InsufficientStake] {
+            let bundle = ledger.gen_signatures_bundle(Some(adversary));
+            let err =
+                collect_witness(&ledger.st, bundle, STAKE_TABLE_CAPACITY_FOR_TEST).unwrap_err();
+            assert!(matches!(err, ProverError::InvalidState(_)), "{adversary:?}");
+        }
+    }
+
     // This test is temporarily ignored. We are unifying the contract deployment in #1071.
     #[async_std::test]
     async fn test_submit_state_and_proof() -> Result<()> {
@@ -688,4 +1691,282 @@ mod test {
         assert_eq!(finalized_l1, new_state);
         Ok(())
     }
+
+    #[async_std::test]
+    async fn test_submit_to_mirror_on_same_chain() -> Result<()> {
+        setup_logging();
+        setup_backtrace();
+
+        let (genesis, _qc_keys, state_keys, st) = init_ledger_for_test();
+
+        // Mine blocks on an interval, so that both updates would be pending at the same time.
+        let anvil = Anvil::new().block_time(1u64).spawn();
+        let (_wallet, contract) = deploy_contract_for_test(&anvil, genesis.clone()).await?;
+        let (_wallet, mirror) = deploy_contract_for_test(&anvil, genesis.clone()).await?;
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&anvil, contract.address());
+        let target = LightClientTarget {
+            provider: config.l1_provider.clone(),
+            address: mirror.address(),
+        };
+        config.mirrors = vec![target.clone()];
+
+        let mut new_state = genesis.clone();
+        new_state.view_num = 5;
+        new_state.block_height = 1;
+        let (pi, proof) = gen_state_proof(&genesis, new_state.clone(), &state_keys, &st);
+
+        // Both updates are sent from the same account on the same chain.
+        let mirror_config = config.for_target(&target);
+        let (primary, mirrored) = futures::join!(
+            submit_state_and_proof(proof.clone(), pi.clone(), &config),
+            submit_state_and_proof(proof, pi, &mirror_config),
+        );
+        primary?;
+        mirrored?;
+        for contract in [contract, mirror] {
+            let finalized: ParsedLightClientState = contract.get_finalized_state().await?.into();
+            assert_eq!(finalized, new_state);
+        }
+        Ok(())
+    }
+
+    /// Wait until `wallet` has `count` transactions pending, and return their hashes.
+    async fn wait_for_pending(wallet: &L1Wallet, count: usize) -> Result<Vec<H256>> {
+        loop {
+            let mut content = wallet.txpool_content().await?;
+            let pending = content
+                .pending
+                .remove(&wallet.address())
+                .unwrap_or_default();
+            if pending.len() == count {
+                return Ok(pending.into_values().map(|tx| tx.hash).collect());
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    #[async_std::test]
+    async fn test_submit_with_pending_transactions() -> Result<()> {
+        setup_logging();
+        setup_backtrace();
+
+        let anvil = Anvil::new().spawn();
+        let genesis = ParsedLightClientState::dummy_genesis();
+        let (wallet, contract) = deploy_contract_for_test(&anvil, genesis.clone()).await?;
+        let mock = LightClientMock::new(contract.address(), wallet.clone());
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&anvil, contract.address());
+        config.resubmission.stuck_timeout = Duration::from_secs(60);
+
+        // Leave transactions pending until the test mines them.
+        wallet
+            .provider()
+            .request::<_, serde_json::Value>("evm_setAutomine", [false])
+            .await?;
+        let state_at = |block_height| {
+            let mut state = genesis.clone();
+            state.block_height = block_height;
+            state
+        };
+        let submit = |block_height: u64| {
+            let contract = contract.clone();
+            let config = config.clone();
+            let call = mock.set_finalized_state(state_at(block_height).into());
+            spawn(async move { send_update(&contract, call, block_height as usize, &config).await })
+        };
+
+        // An update left pending by a previous run, paying far more than the current fees, is
+        // outbid and replaced.
+        let stale = mock
+            .set_finalized_state(state_at(1).into())
+            .gas_price(U256::from(100) * U256::exp10(9))
+            .send()
+            .await?
+            .tx_hash();
+        let update = submit(2);
+        loop {
+            let pending = wait_for_pending(&wallet, 1).await?;
+            if pending != [stale] {
+                break;
+            }
+            sleep(Duration::from_millis(100)).await;
+        }
+        wallet
+            .provider()
+            .request::<_, serde_json::Value>("evm_mine", ())
+            .await?;
+        let receipt = update.await?;
+        assert_ne!(receipt.transaction_hash, stale);
+        assert_eq!(wallet.get_transaction_receipt(stale).await?, None);
+        assert_eq!(read_contract_state(&config).await?.block_height, 2);
+
+        // Other pending transactions are not replaced, and the update is queued behind them.
+        let transfer = wallet
+            .send_transaction(TransactionRequest::pay(anvil.addresses()[1], 1), None)
+            .await?
+            .tx_hash();
+        let update = submit(3);
+        wait_for_pending(&wallet, 2).await?;
+        wallet
+            .provider()
+            .request::<_, serde_json::Value>("evm_mine", ())
+            .await?;
+        update.await?;
+        assert!(wallet.get_transaction_receipt(transfer).await?.is_some());
+        assert_eq!(read_contract_state(&config).await?.block_height, 3);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_submit_while_state_unavailable() -> Result<()> {
+        setup_logging();
+        setup_backtrace();
+
+        let anvil = Anvil::new().spawn();
+        let genesis = ParsedLightClientState::dummy_genesis();
+        let (wallet, contract) = deploy_contract_for_test(&anvil, genesis.clone()).await?;
+        let mock = LightClientMock::new(contract.address(), wallet.clone());
+
+        // Read the contract state from another chain with the same contract at the same address,
+        // which goes away while the update is pending.
+        let reader = Anvil::new().spawn();
+        let (_wallet, replica) = deploy_contract_for_test(&reader, genesis.clone()).await?;
+        assert_eq!(replica.address(), contract.address());
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&reader, contract.address());
+        config.resubmission.stuck_timeout = Duration::from_secs(1);
+
+        wallet
+            .provider()
+            .request::<_, serde_json::Value>("evm_setAutomine", [false])
+            .await?;
+        let mut state = genesis.clone();
+        state.block_height = 1;
+        let call = mock.set_finalized_state(state.clone().into());
+        let update = {
+            let contract = contract.clone();
+            let config = config.clone();
+            spawn(async move { send_update(&contract, call, 1, &config).await })
+        };
+        wait_for_pending(&wallet, 1).await?;
+        drop(reader);
+
+        // The update stays pending through several failed state reads, and still lands.
+        sleep(Duration::from_secs(3)).await;
+        wallet
+            .provider()
+            .request::<_, serde_json::Value>("evm_mine", ())
+            .await?;
+        let receipt = update.await?;
+        assert_eq!(receipt.status, Some(1.into()));
+        let finalized: ParsedLightClientState = contract.get_finalized_state().await?.into();
+        assert_eq!(finalized, state);
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn test_epoch_end_submitted_first() -> Result<()> {
+        setup_logging();
+        setup_backtrace();
+
+        let (genesis, _qc_keys, state_keys, st) = init_ledger_for_test();
+        let anvil = Anvil::new().spawn();
+        let (_wallet, contract) = deploy_contract_for_test(&anvil, genesis.clone()).await?;
+        let mut config = StateProverConfig::default();
+        config.update_l1_info(&anvil, contract.address());
+
+        // The end of the first epoch, and a state in the next epoch whose proof is ready first.
+        let mut ledger = MockLedger::init(MockSystemParam::init(BLOCKS_PER_EPOCH), 5);
+        ledger.elapse_with_block();
+        let witness = ledger.gen_witness(None);
+        let epoch_end = BLOCKS_PER_EPOCH as usize;
+        let later = epoch_end + 5;
+        let mut proofs = HashMap::new();
+        let mut updates = vec![];
+        for (height, epoch, delay) in [(epoch_end, 1, 3), (later, 2, 0)] {
+            let mut new_state = genesis.clone();
+            new_state.view_num = height as u64;
+            new_state.block_height = height as u64;
+            let (pi, proof) = gen_state_proof(&genesis, new_state, &state_keys, &st);
+            proofs.insert(height, (proof, pi, Duration::from_secs(delay)));
+
+            let mut request = witness.clone();
+            request.state.block_height = height;
+            updates.push(ScheduledUpdate {
+                request,
+                epoch,
+                epoch_end: height == epoch_end,
+            });
+        }
+        let backend = Arc::new(ProvingBackend::<SequencerVersion>::Mock(MockProver {
+            vk: keys_for_test().1,
+            proofs,
+            proven: Default::default(),
+        }));
+
+        let metrics = Arc::new(ProverMetrics::new(&PrometheusMetrics::default()));
+        let cache = Arc::new(ProofCache::new(0, None, backend.verifying_key()));
+        let last_dispatched = Arc::new(AtomicUsize::new(0));
+        let (witness_send, witness_recv) = channel::bounded(1);
+        let (proof_send, proof_recv) = channel::unbounded();
+        for i in 0..2 {
+            spawn(proving_worker(
+                i,
+                backend.clone(),
+                cache.clone(),
+                metrics.clone(),
+                last_dispatched.clone(),
+                witness_recv.clone(),
+                proof_send.clone(),
+            ));
+        }
+        drop(proof_send);
+        let submission = spawn(submission_task(
+            config.clone(),
+            metrics.clone(),
+            Some(last_dispatched.clone()),
+            None,
+            proof_recv,
+        ));
+
+        // Dispatching the end of the epoch does not wait for a worker to take it, and no later
+        // state is dispatched until one has.
+        let handoff = Arc::new(AtomicBool::new(false));
+        let mut updates = updates.into_iter();
+        dispatch(
+            updates.next().unwrap(),
+            &witness_send,
+            &witness_recv,
+            &handoff,
+        )?;
+        while handoff.load(Ordering::SeqCst) {
+            sleep(Duration::from_millis(100)).await;
+        }
+        dispatch(
+            updates.next().unwrap(),
+            &witness_send,
+            &witness_recv,
+            &handoff,
+        )?;
+        drop(witness_send);
+        drop(witness_recv);
+        submission.await;
+
+        // The later state was proven first, but it is submitted after the end of the epoch.
+        let ProvingBackend::Mock(prover) = &*backend else {
+            unreachable!()
+        };
+        assert_eq!(*prover.proven.lock().unwrap(), [later, epoch_end]);
+        let submitted = contract
+            .new_state_filter()
+            .from_block(0u64)
+            .query()
+            .await?
+            .into_iter()
+            .map(|event| event.block_height as usize)
+            .collect::<Vec<_>>();
+        assert_eq!(submitted, [epoch_end, later]);
+        Ok(())
+    }
 }
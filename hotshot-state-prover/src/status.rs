@@ -0,0 +1,84 @@
+//! Status of the prover service, reported over HTTP for health checks and debugging.
+
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// How long past its update interval the service may go without checking for a new state before
+/// it is considered unhealthy.
+const HEALTH_GRACE_PERIOD: Duration = Duration::from_secs(300);
+
+/// Whether the proving key has been derived from the SRS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SrsStatus {
+    /// The SRS is still being loaded. This can take several minutes on startup.
+    #[default]
+    Loading,
+    /// Keys are loaded and the service is ready to prove.
+    Loaded,
+}
+
+/// Progress of the prover service.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverStatus {
+    /// Whether the proving key has been loaded.
+    pub srs: SrsStatus,
+    /// HotShot block height of the most recently generated proof.
+    pub last_proven_height: Option<usize>,
+    /// HotShot block height of the most recent light client update submitted by this prover.
+    pub last_submitted_height: Option<usize>,
+    /// L1 transaction of the most recent light client update submitted by this prover.
+    pub last_submitted_tx: Option<H256>,
+    /// Latest HotShot block height with a signed light client state.
+    pub hotshot_height: Option<usize>,
+    /// HotShot block height of the state finalized in the light client contract.
+    pub light_client_height: Option<usize>,
+    /// Number of HotShot blocks the light client contract is behind.
+    pub lag: Option<usize>,
+    /// Unix timestamp, in seconds, at which the service last checked for a new state to prove.
+    pub last_checked: Option<i64>,
+}
+
+impl ProverStatus {
+    /// Record that the service has just checked for a new state to prove.
+    pub fn checked(&mut self) {
+        self.last_checked = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    /// Whether a service which checks for new states every `update_interval` is making progress.
+    ///
+    /// A service which is still loading the SRS is healthy, since it has not started checking yet.
+    pub fn is_healthy(&self, update_interval: Duration) -> bool {
+        self.is_healthy_at(OffsetDateTime::now_utc().unix_timestamp(), update_interval)
+    }
+
+    fn is_healthy_at(&self, now: i64, update_interval: Duration) -> bool {
+        if self.srs == SrsStatus::Loading {
+            return true;
+        }
+        let Some(last_checked) = self.last_checked else {
+            return true;
+        };
+        let max_delay = (update_interval + HEALTH_GRACE_PERIOD).as_secs() as i64;
+        now - last_checked <= max_delay
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prover_health() {
+        let interval = Duration::from_secs(60);
+        let mut status = ProverStatus::default();
+        assert!(status.is_healthy_at(1_000_000, interval));
+
+        status.srs = SrsStatus::Loaded;
+        status.last_checked = Some(1_000);
+        assert!(status.is_healthy_at(1_000 + 360, interval));
+        assert!(!status.is_healthy_at(1_000 + 361, interval));
+    }
+}
@@ -0,0 +1,408 @@
+//! Submission of light client updates to L1.
+//!
+//! A light client update is a large transaction, and when L1 fees spike it can sit in the mempool
+//! long after the state it proves is outdated. Rather than waiting indefinitely, the prover watches
+//! each update it sends and, if it is not mined within [`ResubmissionPolicy::stuck_timeout`],
+//! replaces it with a copy paying higher fees, up to a configurable cap.
+//!
+//! A light client update left pending by a previous run of the prover is replaced rather than left
+//! to block every later update behind it: the new update is sent at the nonce of the pending one,
+//! outbidding its fees. Other transactions pending from the same account are not touched, and the
+//! update is queued behind them instead. If the node does not expose its transaction pool, the
+//! prover cannot tell which transactions are pending, and replaces whatever is pending at the
+//! account's latest mined nonce, raising its fees until the replacement is accepted.
+//!
+//! Updates to several contracts on the same chain, such as a mirror deployed next to the primary
+//! contract, are sent from the same account. Each would otherwise be assigned the same nonce and
+//! replace the other, so the prover sends one update at a time from each account on each chain,
+//! and the next is only sent once the previous one is mined or given up on.
+//!
+//! Before each resubmission the prover checks the contract: if an update from another prover has
+//! already advanced it to the same or a newer state, the pending transaction is cancelled, rather
+//! than left to be mined and revert.
+
+use crate::service::{read_contract_state, L1Wallet, ProverError, StateProverConfig};
+use anyhow::anyhow;
+use async_std::{sync::Mutex, task::sleep};
+use contract_bindings::light_client::{LightClient, LightClientErrors};
+use ethers::{
+    contract::ContractCall,
+    providers::{Middleware, MiddlewareError},
+    signers::Signer,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes, Transaction,
+        TransactionReceipt, H256, U256,
+    },
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
+};
+
+/// How often to check whether a submitted update has been mined.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Gas limit of a plain transfer, used to cancel a pending update.
+const CANCEL_GAS: u64 = 21_000;
+
+/// How the prover resubmits light client updates which are not mined in time.
+#[derive(Clone, Debug)]
+pub struct ResubmissionPolicy {
+    /// How long to wait for an update to be mined before resubmitting it with higher fees.
+    pub stuck_timeout: Duration,
+    /// Percentage by which fees are increased on each resubmission.
+    ///
+    /// Most nodes only accept a replacement transaction which pays at least 10% more than the
+    /// transaction it replaces.
+    pub fee_bump_percent: u64,
+    /// Maximum fee per gas, in wei, the prover will pay. Fees are never bumped past this cap.
+    pub max_fee_per_gas: Option<U256>,
+    /// Number of times an update may be resubmitted before giving up.
+    pub max_resubmissions: usize,
+}
+
+impl Default for ResubmissionPolicy {
+    fn default() -> Self {
+        Self {
+            stuck_timeout: Duration::from_secs(180),
+            fee_bump_percent: 20,
+            max_fee_per_gas: None,
+            max_resubmissions: 10,
+        }
+    }
+}
+
+/// Send the light client update `call`, proving the state at `height`, and wait for it to be mined.
+///
+/// Fails with [`ProverError::Superseded`] if another update to `height` or later lands first.
+pub(crate) async fn send_update(
+    contract: &LightClient<L1Wallet>,
+    call: ContractCall<L1Wallet, ()>,
+    height: usize,
+    config: &StateProverConfig,
+) -> Result<TransactionReceipt, ProverError> {
+    let policy = &config.resubmission;
+    let l1 = contract.client();
+    let account = account_lock(l1.signer().chain_id(), l1.address());
+    let _guard = account.lock().await;
+    check_not_superseded(height, config).await?;
+
+    // Estimate gas through the contract call, so that a revert is decoded into a contract error.
+    let gas = call.estimate_gas().await.map_err(|err| {
+        match err.decode_contract_revert::<LightClientErrors>() {
+            Some(revert) => ProverError::ContractError(anyhow!("contract revert: {revert:?}")),
+            None => l1_error(err),
+        }
+    })?;
+    let (nonce, replaced) = choose_nonce(&l1, contract.address()).await?;
+
+    let mut tx = call.tx;
+    tx.set_nonce(nonce);
+    tx.set_gas(gas);
+    l1.fill_transaction(&mut tx, None).await.map_err(l1_error)?;
+    if let Some(replaced) = &replaced {
+        outbid(&mut tx, replaced, policy);
+    }
+    cap_fees(&mut tx, policy.max_fee_per_gas);
+
+    // Any of the transactions we send with this nonce may be the one that is mined.
+    let mut sent = vec![send_replacement(&l1, &mut tx, policy).await?];
+    let mut attempts = 0;
+    loop {
+        if let Some(receipt) = wait_for_receipt(&l1, &sent, policy.stuck_timeout).await {
+            if receipt.status != Some(1.into()) {
+                return Err(ProverError::ContractError(anyhow!(
+                    "light client update {:x} reverted",
+                    receipt.transaction_hash
+                )));
+            }
+            return Ok(receipt);
+        }
+
+        match check_not_superseded(height, config).await {
+            Ok(()) => {}
+            Err(err @ ProverError::Superseded(_)) => {
+                cancel(&l1, &tx, policy).await;
+                return Err(err);
+            }
+            // A failed read says nothing about whether the update is still needed, so keep trying
+            // to land it rather than cancelling an update which may be about to be mined.
+            Err(err) => tracing::warn!(
+                height,
+                "cannot check whether the light client update is still needed: {err}"
+            ),
+        }
+        if attempts >= policy.max_resubmissions {
+            return Err(ProverError::ContractError(anyhow!(
+                "light client update not mined after {attempts} resubmissions"
+            )));
+        }
+        attempts += 1;
+        if !bump_fees(&mut tx, policy) {
+            tracing::warn!(
+                height,
+                "light client update is stuck, but fees are at the cap"
+            );
+            continue;
+        }
+        tracing::warn!(
+            height,
+            attempts,
+            gas_price = ?tx.gas_price(),
+            "light client update is stuck, resubmitting with higher fees"
+        );
+        match send(&l1, &tx).await {
+            Ok(hash) => sent.push(hash),
+            // The replacement may be rejected if one of the earlier transactions was mined in the
+            // meantime; the next poll will find its receipt.
+            Err(err) => tracing::warn!("cannot resubmit light client update: {err}"),
+        }
+    }
+}
+
+/// The lock serializing submissions from `account` on the chain with ID `chain_id`.
+fn account_lock(chain_id: u64, account: Address) -> Arc<Mutex<()>> {
+    static LOCKS: OnceLock<std::sync::Mutex<HashMap<(u64, Address), Arc<Mutex<()>>>>> =
+        OnceLock::new();
+    LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry((chain_id, account))
+        .or_default()
+        .clone()
+}
+
+/// Choose the nonce for a light client update to `contract`, and the pending transaction it
+/// replaces, if known.
+async fn choose_nonce(
+    l1: &L1Wallet,
+    contract: Address,
+) -> Result<(U256, Option<Transaction>), ProverError> {
+    let nonce = l1
+        .get_transaction_count(l1.address(), Some(BlockNumber::Latest.into()))
+        .await
+        .map_err(l1_error)?;
+    let pending_nonce = l1
+        .get_transaction_count(l1.address(), Some(BlockNumber::Pending.into()))
+        .await
+        .map_err(l1_error)?;
+    if pending_nonce <= nonce {
+        return Ok((nonce, None));
+    }
+
+    let pending = match l1.txpool_content().await {
+        Ok(mut content) => content.pending.remove(&l1.address()).unwrap_or_default(),
+        Err(err) => {
+            tracing::warn!(
+                %nonce,
+                %pending_nonce,
+                "account has pending transactions, but the transaction pool is not available, \
+                 replacing them: {err}"
+            );
+            return Ok((nonce, None));
+        }
+    };
+    match pending
+        .into_values()
+        .filter(|tx| tx.to == Some(contract))
+        .min_by_key(|tx| tx.nonce)
+    {
+        Some(update) => {
+            tracing::warn!(
+                nonce = %update.nonce,
+                hash = ?update.hash,
+                "replacing pending light client update"
+            );
+            Ok((update.nonce, Some(update)))
+        }
+        None => {
+            tracing::warn!(
+                %nonce,
+                %pending_nonce,
+                "account has other pending transactions, queueing update behind them"
+            );
+            Ok((pending_nonce, None))
+        }
+    }
+}
+
+/// Raise the fees of `tx` enough for nodes to accept it in place of `replaced`.
+fn outbid(tx: &mut TypedTransaction, replaced: &Transaction, policy: &ResubmissionPolicy) {
+    // Nodes require a replacement to pay at least 10% more than the transaction it replaces.
+    let bump = |fee: U256| fee + fee * policy.fee_bump_percent.max(10) / 100;
+    if let Some(inner) = tx.as_eip1559_mut() {
+        let max_fee = replaced.max_fee_per_gas.or(replaced.gas_price).map(bump);
+        let priority_fee = replaced
+            .max_priority_fee_per_gas
+            .or(replaced.gas_price)
+            .map(bump);
+        inner.max_fee_per_gas = inner.max_fee_per_gas.max(max_fee);
+        inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.max(priority_fee);
+    } else if let Some(gas_price) = replaced.gas_price.or(replaced.max_fee_per_gas) {
+        let gas_price = bump(gas_price).max(tx.gas_price().unwrap_or_default());
+        tx.set_gas_price(gas_price);
+    }
+}
+
+/// Send `tx`, raising its fees until it is accepted if it is underpriced as a replacement for a
+/// transaction pending at the same nonce.
+async fn send_replacement(
+    l1: &L1Wallet,
+    tx: &mut TypedTransaction,
+    policy: &ResubmissionPolicy,
+) -> Result<H256, ProverError> {
+    let mut bumps = 0;
+    loop {
+        match l1.send_transaction(tx.clone(), None).await {
+            Ok(pending) => {
+                let hash = pending.tx_hash();
+                tracing::info!("sent light client update {hash:x}");
+                return Ok(hash);
+            }
+            Err(err) if is_underpriced(&err) && bumps < policy.max_resubmissions => {
+                if !bump_fees(tx, policy) {
+                    return Err(l1_error(err));
+                }
+                bumps += 1;
+                tracing::warn!(
+                    bumps,
+                    gas_price = ?tx.gas_price(),
+                    "light client update is underpriced as a replacement, raising fees"
+                );
+            }
+            Err(err) => return Err(l1_error(err)),
+        }
+    }
+}
+
+/// Whether a transaction was rejected for not paying enough to replace a pending transaction.
+fn is_underpriced(err: &impl MiddlewareError) -> bool {
+    err.as_error_response()
+        .is_some_and(|err| err.message.contains("underpriced"))
+}
+
+/// Fail with [`ProverError::Superseded`] if the contract already has a state at `height` or later.
+async fn check_not_superseded(
+    height: usize,
+    config: &StateProverConfig,
+) -> Result<(), ProverError> {
+    let current = read_contract_state(config).await?.block_height;
+    if current >= height {
+        return Err(ProverError::Superseded(current));
+    }
+    Ok(())
+}
+
+async fn send(l1: &L1Wallet, tx: &TypedTransaction) -> Result<H256, ProverError> {
+    let hash = l1
+        .send_transaction(tx.clone(), None)
+        .await
+        .map_err(l1_error)?
+        .tx_hash();
+    tracing::info!("sent light client update {hash:x}");
+    Ok(hash)
+}
+
+/// Wait up to `timeout` for any of the transactions in `sent` to be mined.
+async fn wait_for_receipt(
+    l1: &L1Wallet,
+    sent: &[H256],
+    timeout: Duration,
+) -> Option<TransactionReceipt> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        for hash in sent {
+            match l1.get_transaction_receipt(*hash).await {
+                Ok(Some(receipt)) => return Some(receipt),
+                Ok(None) => {}
+                Err(err) => tracing::warn!("cannot get receipt for {hash:x}: {err}"),
+            }
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Replace a pending update with an empty transfer to ourselves, so it is not mined and reverted.
+async fn cancel(l1: &L1Wallet, tx: &TypedTransaction, policy: &ResubmissionPolicy) {
+    let mut cancel = tx.clone();
+    cancel
+        .set_to(l1.address())
+        .set_data(Bytes::default())
+        .set_value(0)
+        .set_gas(CANCEL_GAS);
+    bump_fees(&mut cancel, policy);
+    match send(l1, &cancel).await {
+        Ok(hash) => tracing::info!("cancelling superseded light client update with {hash:x}"),
+        // The update itself may have been mined in the meantime, in which case it reverts, but
+        // there is nothing left to cancel.
+        Err(err) => tracing::warn!("cannot cancel superseded light client update: {err}"),
+    }
+}
+
+/// Increase the fees of `tx` according to `policy`. Returns `false` if fees are already capped.
+fn bump_fees(tx: &mut TypedTransaction, policy: &ResubmissionPolicy) -> bool {
+    let bump = |fee: U256| fee + fee * policy.fee_bump_percent / 100;
+    let before = tx.gas_price();
+    if let Some(inner) = tx.as_eip1559_mut() {
+        inner.max_fee_per_gas = inner.max_fee_per_gas.map(bump);
+        inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(bump);
+    } else if let Some(gas_price) = tx.gas_price() {
+        tx.set_gas_price(bump(gas_price));
+    }
+    cap_fees(tx, policy.max_fee_per_gas);
+    tx.gas_price() != before
+}
+
+/// Limit the fees of `tx` to `cap`, if any.
+fn cap_fees(tx: &mut TypedTransaction, cap: Option<U256>) {
+    let Some(cap) = cap else {
+        return;
+    };
+    if let Some(inner) = tx.as_eip1559_mut() {
+        inner.max_fee_per_gas = inner.max_fee_per_gas.map(|fee| fee.min(cap));
+        inner.max_priority_fee_per_gas = inner.max_priority_fee_per_gas.map(|fee| fee.min(cap));
+    } else if let Some(gas_price) = tx.gas_price() {
+        tx.set_gas_price(gas_price.min(cap));
+    }
+}
+
+fn l1_error(err: impl Display) -> ProverError {
+    ProverError::ContractError(anyhow!("{err}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethers::types::{Eip1559TransactionRequest, TransactionRequest};
+
+    #[test]
+    fn test_bump_fees_up_to_cap() {
+        let policy = ResubmissionPolicy {
+            fee_bump_percent: 50,
+            max_fee_per_gas: Some(200.into()),
+            ..Default::default()
+        };
+
+        let mut tx: TypedTransaction = TransactionRequest::new().gas_price(100).into();
+        assert!(bump_fees(&mut tx, &policy));
+        assert_eq!(tx.gas_price(), Some(150.into()));
+        assert!(bump_fees(&mut tx, &policy));
+        assert_eq!(tx.gas_price(), Some(200.into()));
+        assert!(!bump_fees(&mut tx, &policy));
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .max_fee_per_gas(100)
+            .max_priority_fee_per_gas(10)
+            .into();
+        assert!(bump_fees(&mut tx, &policy));
+        let inner = tx.as_eip1559_mut().unwrap();
+        assert_eq!(inner.max_fee_per_gas, Some(150.into()));
+        assert_eq!(inner.max_priority_fee_per_gas, Some(15.into()));
+    }
+}
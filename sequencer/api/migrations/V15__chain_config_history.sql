@@ -0,0 +1,7 @@
+-- Each chain config this node has seen decided, keyed by the height of the first block which used
+-- it.
+CREATE TABLE chain_config_history (
+    height BIGINT PRIMARY KEY,
+    view   BIGINT,
+    config JSONB
+);
@@ -1,12 +1,17 @@
 use self::data_source::StateSignatureDataSource;
 use crate::{
-    network, persistence::SequencerPersistence, state::ValidatedState,
-    state_signature::StateSigner, Node, NodeState, SeqTypes, SequencerContext, Transaction,
+    network,
+    persistence::SequencerPersistence,
+    state::ValidatedState,
+    state_signature::StateSigner,
+    tx_trace::{TxTrace, TxTracer},
+    ChainConfig, ChainConfigActivation, Node, NodeState, SeqTypes, SequencerContext, Transaction,
 };
 use async_once_cell::Lazy;
 use async_std::sync::{Arc, RwLock};
 use async_trait::async_trait;
-use data_source::{StateDataSource, SubmitDataSource};
+use committable::{Commitment, Committable};
+use data_source::{ChainConfigDataSource, StateDataSource, SubmitDataSource};
 use derivative::Derivative;
 use futures::{
     future::{BoxFuture, Future, FutureExt},
@@ -39,6 +44,11 @@ struct ConsensusState<N: network::Type, P: SequencerPersistence, Ver: StaticVers
 
     #[derivative(Debug = "ignore")]
     handle: SystemContextHandle<SeqTypes, Node<N, P>>,
+
+    #[derivative(Debug = "ignore")]
+    persistence: Arc<RwLock<P>>,
+
+    tx_tracer: Arc<TxTracer>,
 }
 
 impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static>
@@ -50,6 +60,8 @@ impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static
             event_streamer: ctx.get_event_streamer(),
             node_state: ctx.node_state(),
             handle: ctx.consensus().clone(),
+            persistence: ctx.persistence(),
+            tx_tracer: ctx.tx_tracer(),
         }
     }
 }
@@ -96,6 +108,14 @@ impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static
     async fn node_state(&self) -> &NodeState {
         &self.consensus.as_ref().get().await.get_ref().node_state
     }
+
+    async fn persistence(&self) -> &RwLock<P> {
+        &self.consensus.as_ref().get().await.get_ref().persistence
+    }
+
+    async fn tx_tracer(&self) -> &TxTracer {
+        &self.consensus.as_ref().get().await.get_ref().tx_tracer
+    }
 }
 
 type StorageState<N, P, D, Ver> = ExtensibleDataSource<D, ApiState<N, P, Ver>>;
@@ -126,15 +146,49 @@ impl<
     async fn submit(&self, tx: Transaction) -> anyhow::Result<()> {
         self.as_ref().submit(tx).await
     }
+
+    async fn chain_config(&self) -> ChainConfig {
+        self.as_ref().chain_config().await
+    }
+
+    async fn trace(&self, hash: Commitment<Transaction>) -> Option<TxTrace> {
+        self.as_ref().trace(hash).await
+    }
 }
 
 impl<N: network::Type, Ver: StaticVersionType + 'static, P: SequencerPersistence>
     SubmitDataSource<N, P> for ApiState<N, P, Ver>
 {
     async fn submit(&self, tx: Transaction) -> anyhow::Result<()> {
-        self.consensus().await.submit_transaction(tx).await?;
+        let hash = tx.commit();
+        let tracer = self.tx_tracer().await;
+
+        // Reject transactions which can never be included, rather than letting them sit in the
+        // mempool until they are silently dropped.
+        if let Err(err) = self
+            .node_state()
+            .await
+            .chain_config()
+            .check_transaction(&tx)
+        {
+            tracer.rejected(hash, &err).await;
+            return Err(err.into());
+        }
+        if let Err(err) = self.consensus().await.submit_transaction(tx).await {
+            tracer.rejected(hash, &err).await;
+            return Err(err.into());
+        }
+        tracer.submitted(hash).await;
         Ok(())
     }
+
+    async fn chain_config(&self) -> ChainConfig {
+        *self.node_state().await.chain_config()
+    }
+
+    async fn trace(&self, hash: Commitment<Transaction>) -> Option<TxTrace> {
+        self.tx_tracer().await.get(hash).await
+    }
 }
 
 impl<
@@ -165,6 +219,31 @@ impl<N: network::Type, Ver: StaticVersionType + 'static, P: SequencerPersistence
     }
 }
 
+impl<
+        N: network::Type,
+        D: Send + Sync,
+        Ver: StaticVersionType + 'static,
+        P: SequencerPersistence,
+    > ChainConfigDataSource for StorageState<N, P, D, Ver>
+{
+    async fn chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>> {
+        self.as_ref().chain_config_history().await
+    }
+}
+
+impl<N: network::Type, Ver: StaticVersionType + 'static, P: SequencerPersistence>
+    ChainConfigDataSource for ApiState<N, P, Ver>
+{
+    async fn chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>> {
+        self.persistence()
+            .await
+            .read()
+            .await
+            .load_chain_config_history()
+            .await
+    }
+}
+
 #[async_trait]
 impl<N: network::Type, D: Sync, Ver: StaticVersionType + 'static, P: SequencerPersistence>
     StateSignatureDataSource<N> for StorageState<N, P, D, Ver>
@@ -187,15 +266,16 @@ impl<N: network::Type, Ver: StaticVersionType + 'static, P: SequencerPersistence
 mod test_helpers {
     use super::*;
     use crate::{
-        api::endpoints::{AccountQueryData, BlocksFrontier},
+        api::endpoints::{AccountQueryData, BlocksFrontier, SubmitLimits},
         catchup::{mock::MockStateCatchup, StateCatchup},
         persistence::{no_storage::NoStorage, SequencerPersistence},
         state::BlockMerkleTree,
         testing::{run_test_builder, wait_for_decide_on_handle, TestConfig},
+        tx_trace::{TxStage, TxStageEvent},
     };
     use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
     use async_std::task::sleep;
-    use committable::Committable;
+    use committable::{Commitment, Committable};
     use es_version::{SequencerVersion, SEQUENCER_VERSION};
     use ethers::prelude::Address;
     use futures::{
@@ -213,7 +293,7 @@ mod test_helpers {
     use portpicker::pick_unused_port;
     use std::time::Duration;
     use surf_disco::Client;
-    use tide_disco::error::ServerError;
+    use tide_disco::{error::ServerError, Error as _, StatusCode};
 
     pub const STAKE_TABLE_CAPACITY_FOR_TEST: usize = 10;
 
@@ -393,6 +473,57 @@ mod test_helpers {
 
         // Wait for a Decide event containing transaction matching the one we sent
         wait_for_decide_on_handle(&mut events, &txn).await;
+
+        // Transactions which can never fit in a block are rejected with an explanation.
+        let limits: SubmitLimits = client.get("submit/limits").send().await.unwrap();
+        let too_large = Transaction::new(
+            Default::default(),
+            vec![0; limits.max_block_size as usize + 1],
+        );
+        let err = client
+            .post::<Commitment<Transaction>>("submit/submit")
+            .body_json(&too_large)
+            .unwrap()
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::BadRequest);
+        assert!(
+            err.to_string().contains("exceeds the maximum block size"),
+            "{err}"
+        );
+
+        // Both transactions can be traced through this node.
+        let trace: TxTrace = client
+            .get(&format!("submit/trace/{hash}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(trace.events[0].stage, TxStage::Submitted);
+        let trace: TxTrace = client
+            .get(&format!("submit/trace/{}", too_large.commit()))
+            .send()
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                trace.events[..],
+                [TxStageEvent {
+                    stage: TxStage::Rejected { .. },
+                    ..
+                }]
+            ),
+            "{trace:?}"
+        );
+        let err = client
+            .get::<TxTrace>(&format!(
+                "submit/trace/{}",
+                Transaction::new(Default::default(), vec![5]).commit()
+            ))
+            .send()
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::NotFound);
     }
 
     /// Test the state signature API.
@@ -757,7 +888,7 @@ mod test {
     };
     use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
     use async_std::task::sleep;
-    use committable::Commitment;
+    use committable::{Commitment, Committable};
     use es_version::{SequencerVersion, SEQUENCER_VERSION};
     use futures::{
         future::{self, join_all},
@@ -1123,4 +1254,69 @@ mod test {
             .unwrap();
         assert_eq!(chain, new_chain);
     }
+
+    #[async_std::test]
+    async fn test_chain_config_history() {
+        setup_logging();
+        setup_backtrace();
+
+        let storage =
+            join_all((0..TestConfig::NUM_NODES).map(|_| SqlDataSource::create_storage())).await;
+        let persistence = join_all(
+            storage
+                .iter()
+                .map(<SqlDataSource as TestableSequencerDataSource>::connect),
+        )
+        .await
+        .try_into()
+        .unwrap();
+        let port = pick_unused_port().unwrap();
+        let mut network = TestNetwork::new(
+            SqlDataSource::options(&storage[0], options::Http { port }.into()),
+            persistence,
+        )
+        .await;
+        let client: Client<ServerError, SequencerVersion> =
+            Client::new(format!("http://localhost:{port}").parse().unwrap());
+        client.connect(None).await;
+
+        // Wait until some blocks have been decided.
+        let chain: Vec<LeafQueryData<SeqTypes>> = client
+            .socket("availability/stream/leaves/0")
+            .subscribe()
+            .await
+            .unwrap()
+            .take(3)
+            .try_collect()
+            .await
+            .unwrap();
+        network.stop_consensus().await;
+
+        // The history is recorded as leaves are decided, which may lag behind the query service.
+        let history = loop {
+            let history = client
+                .get::<Vec<ChainConfigActivation>>("config/chain-config-history")
+                .send()
+                .await
+                .unwrap();
+            if !history.is_empty() {
+                break history;
+            }
+            tracing::info!("waiting for chain config history");
+            sleep(Duration::from_secs(1)).await;
+        };
+
+        // The config never changes, so the history is a single activation of the config used by
+        // every block.
+        assert_eq!(history.len(), 1, "{history:?}");
+        let activation = history[0];
+        let leaf = &chain[activation.height as usize];
+        assert_eq!(activation.view, leaf.leaf().get_view_number().get_u64());
+        for leaf in &chain {
+            assert_eq!(
+                leaf.leaf().get_block_header().chain_config.commit(),
+                activation.config.commit()
+            );
+        }
+    }
 }
@@ -7,10 +7,12 @@ use crate::{
     network,
     persistence::{self, SequencerPersistence},
     state::ValidatedState,
-    SeqTypes, Transaction,
+    tx_trace::TxTrace,
+    ChainConfig, ChainConfigActivation, SeqTypes, Transaction,
 };
 use async_std::sync::Arc;
 use async_trait::async_trait;
+use committable::Commitment;
 use hotshot_query_service::{
     availability::AvailabilityDataSource,
     data_source::{UpdateDataSource, VersionedDataSource},
@@ -82,6 +84,12 @@ pub fn provider<Ver: StaticVersionType + 'static>(
 #[trait_variant::make(SubmitDataSource: Send)]
 pub(crate) trait LocalSubmitDataSource<N: network::Type, P: SequencerPersistence> {
     async fn submit(&self, tx: Transaction) -> anyhow::Result<()>;
+
+    /// The chain config whose limits submitted transactions are checked against.
+    async fn chain_config(&self) -> ChainConfig;
+
+    /// The stages passed so far by a transaction recently submitted through this node.
+    async fn trace(&self, hash: Commitment<Transaction>) -> Option<TxTrace>;
 }
 
 #[async_trait]
@@ -95,6 +103,12 @@ pub(crate) trait LocalStateDataSource {
     async fn get_undecided_state(&self, view: ViewNumber) -> Option<Arc<ValidatedState>>;
 }
 
+#[trait_variant::make(ChainConfigDataSource: Send)]
+pub(crate) trait LocalChainConfigDataSource {
+    /// Every chain config this node has seen activated, ordered by activation height.
+    async fn chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>>;
+}
+
 #[cfg(test)]
 pub(crate) mod testing {
     use super::super::Options;
@@ -2,7 +2,8 @@
 
 use super::{
     data_source::{
-        SequencerDataSource, StateDataSource, StateSignatureDataSource, SubmitDataSource,
+        ChainConfigDataSource, SequencerDataSource, StateDataSource, StateSignatureDataSource,
+        SubmitDataSource,
     },
     StorageState,
 };
@@ -11,7 +12,7 @@ use crate::{
     network,
     persistence::SequencerPersistence,
     state::{BlockMerkleTree, FeeAccountProof, ValidatedState},
-    NamespaceId, SeqTypes, Transaction,
+    NamespaceId, SeqTypes, SizeLimitError, Transaction,
 };
 use anyhow::Result;
 use async_std::sync::{Arc, RwLock};
@@ -154,6 +155,14 @@ where
     )?;
     Ok(api)
 }
+
+/// Limits which submitted transactions must satisfy.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SubmitLimits {
+    /// Maximum size in bytes of a block, and thus of a single transaction payload.
+    pub max_block_size: u64,
+}
+
 pub(super) fn submit<N, P, S, Ver: StaticVersionType + 'static>() -> Result<Api<S, Error, Ver>>
 where
     N: network::Type,
@@ -173,10 +182,32 @@ where
             state
                 .submit(tx)
                 .await
-                .map_err(|err| Error::internal(err.to_string()))?;
+                .map_err(|err| match err.downcast_ref::<SizeLimitError>() {
+                    Some(err) => Error::catch_all(StatusCode::BadRequest, err.to_string()),
+                    None => Error::internal(err.to_string()),
+                })?;
             Ok(hash)
         }
         .boxed()
+    })?
+    .get("limits", |_, state| {
+        async move {
+            let chain_config = state.chain_config().await;
+            Ok(SubmitLimits {
+                max_block_size: chain_config.max_block_size(),
+            })
+        }
+        .boxed()
+    })?
+    .get("trace", |req, state| {
+        async move {
+            let hash = req.blob_param("hash").map_err(Error::from_request_error)?;
+            state.trace(hash).await.ok_or(Error::catch_all(
+                StatusCode::NotFound,
+                format!("transaction {hash} was not recently submitted to this node"),
+            ))
+        }
+        .boxed()
     })?;
 
     Ok(api)
@@ -285,6 +316,27 @@ where
     Ok(api)
 }
 
+pub(super) fn config<S, Ver: StaticVersionType + 'static>(_: Ver) -> Result<Api<S, Error, Ver>>
+where
+    S: 'static + Send + Sync + ReadState,
+    S::State: Send + Sync + ChainConfigDataSource,
+{
+    let toml = toml::from_str::<toml::Value>(include_str!("../../api/config.toml"))?;
+    let mut api = Api::<S, Error, Ver>::new(toml)?;
+
+    api.get("chain_config_history", |_, state| {
+        async move {
+            state
+                .chain_config_history()
+                .await
+                .map_err(|err| Error::internal(format!("{err:#}")))
+        }
+        .boxed()
+    })?;
+
+    Ok(api)
+}
+
 type MerklizedStateApi<N, P, D, Ver> = Api<AvailState<N, P, D, Ver>, merklized_state::Error, Ver>;
 pub(super) fn merklized_state<N, P, D, S, Ver: StaticVersionType + 'static, const ARITY: usize>(
     _: Ver,
@@ -45,6 +45,7 @@ mod impl_testable_data_source {
         async fn connect(storage: &Self::Storage) -> Self::Persistence {
             Options {
                 path: storage.path().into(),
+                encryption: Default::default(),
             }
             .create()
             .await
@@ -56,6 +57,7 @@ mod impl_testable_data_source {
                 Default::default(),
                 Options {
                     path: storage.path().into(),
+                    encryption: Default::default(),
                 },
             )
         }
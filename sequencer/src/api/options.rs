@@ -2,7 +2,8 @@
 
 use super::{
     data_source::{
-        provider, SequencerDataSource, StateDataSource, StateSignatureDataSource, SubmitDataSource,
+        provider, ChainConfigDataSource, SequencerDataSource, StateDataSource,
+        StateSignatureDataSource, SubmitDataSource,
     },
     endpoints, fs, sql,
     update::update_loop,
@@ -13,6 +14,7 @@ use crate::{
     network,
     persistence::{self, SequencerPersistence},
     state::{update_state_storage_loop, BlockMerkleTree, FeeMerkleTree},
+    tx_trace::DEFAULT_TX_TRACE_CAPACITY,
 };
 use anyhow::bail;
 use async_std::sync::{Arc, RwLock};
@@ -138,10 +140,14 @@ impl Options {
                 .await
                 .expect("context initialized and sent over channel")
         });
+        let tx_trace_capacity = self.submit.map(|opt| opt.tx_trace_capacity);
         let init_context = move |metrics| {
             let fut = init_context(metrics);
             async move {
                 let ctx = fut.await;
+                if let Some(capacity) = tx_trace_capacity {
+                    ctx.tx_tracer().set_capacity(capacity).await;
+                }
                 if send_ctx.send(super::ConsensusState::from(&ctx)).is_err() {
                     tracing::warn!("API server exited without receiving context");
                 }
@@ -373,8 +379,12 @@ impl Options {
     where
         S: 'static + Send + Sync + ReadState + WriteState,
         P: SequencerPersistence,
-        S::State:
-            Send + Sync + SubmitDataSource<N, P> + StateSignatureDataSource<N> + StateDataSource,
+        S::State: Send
+            + Sync
+            + SubmitDataSource<N, P>
+            + StateSignatureDataSource<N>
+            + StateDataSource
+            + ChainConfigDataSource,
         N: network::Type,
     {
         let bind_version = Ver::instance();
@@ -394,6 +404,9 @@ impl Options {
         let state_signature_api = endpoints::state_signature(bind_version)?;
         app.register_module("state-signature", state_signature_api)?;
 
+        let config_api = endpoints::config(bind_version)?;
+        app.register_module("config", config_api)?;
+
         Ok(())
     }
 
@@ -452,8 +465,24 @@ pub struct Http {
 }
 
 /// Options for the submission API module.
-#[derive(Parser, Clone, Copy, Debug, Default)]
-pub struct Submit;
+#[derive(Parser, Clone, Copy, Debug)]
+pub struct Submit {
+    /// Number of recently submitted transactions whose progress is traced.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_TX_TRACE_CAPACITY",
+        default_value_t = DEFAULT_TX_TRACE_CAPACITY
+    )]
+    pub tx_trace_capacity: usize,
+}
+
+impl Default for Submit {
+    fn default() -> Self {
+        Self {
+            tx_trace_capacity: DEFAULT_TX_TRACE_CAPACITY,
+        }
+    }
+}
 
 /// Options for the status API module.
 #[derive(Parser, Clone, Copy, Debug, Default)]
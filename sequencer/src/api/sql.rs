@@ -24,9 +24,9 @@ mod impl_testable_data_source {
     use super::*;
     use crate::{
         api::{self, data_source::testing::TestableSequencerDataSource},
-        persistence::PersistenceOptions,
+        persistence::{self, PersistenceOptions},
     };
-    use hotshot_query_service::data_source::storage::sql::{testing::TmpDb, SqlStorage};
+    use hotshot_query_service::data_source::storage::sql::testing::TmpDb;
 
     fn tmp_options(db: &TmpDb) -> Options {
         Options {
@@ -41,7 +41,7 @@ mod impl_testable_data_source {
     #[async_trait]
     impl TestableSequencerDataSource for DataSource {
         type Storage = TmpDb;
-        type Persistence = SqlStorage;
+        type Persistence = persistence::sql::Persistence;
 
         async fn create_storage() -> Self::Storage {
             TmpDb::init().await
@@ -4,11 +4,11 @@ use super::{data_source::SequencerDataSource, StorageState};
 use crate::{network, persistence::SequencerPersistence, SeqTypes};
 use async_std::sync::{Arc, RwLock};
 use futures::stream::{Stream, StreamExt};
-use hotshot::types::Event;
+use hotshot::types::{Event, EventType};
 use hotshot_query_service::data_source::{UpdateDataSource, VersionedDataSource};
 use vbs::version::StaticVersionType;
 
-pub(super) async fn update_loop<N, P, D, Ver: StaticVersionType>(
+pub(super) async fn update_loop<N, P, D, Ver: StaticVersionType + 'static>(
     state: Arc<RwLock<StorageState<N, P, D, Ver>>>,
     mut events: impl Stream<Item = Event<SeqTypes>> + Unpin,
 ) where
@@ -30,6 +30,13 @@ pub(super) async fn update_loop<N, P, D, Ver: StaticVersionType>(
                 "failed to update API state",
             );
             state.revert().await;
+        } else if let EventType::Decide { leaf_chain, .. } = &event.event {
+            // Transactions are traced as consensus events arrive; here we only learn how far the
+            // query service has caught up.
+            if let Some(info) = leaf_chain.first() {
+                let height = info.leaf.get_block_header().height;
+                state.as_ref().tx_tracer().await.indexed(height).await;
+            }
         }
     }
     tracing::warn!("end of HotShot event stream, updater task will exit");
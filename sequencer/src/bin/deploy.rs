@@ -1,19 +1,21 @@
-use anyhow::Context;
+use anyhow::{ensure, Context};
 use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
 use async_std::sync::Arc;
-use clap::Parser;
-use contract_bindings::{
-    erc1967_proxy::ERC1967Proxy, hot_shot::HotShot, light_client::LightClient,
-};
+use clap::{parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser};
 use ethers::prelude::{coins_bip39::English, *};
-use futures::future::FutureExt;
 use hotshot_stake_table::config::STAKE_TABLE_CAPACITY;
 use hotshot_state_prover::service::light_client_genesis;
 use sequencer_utils::deployer::{
-    deploy_light_client_contract, deploy_mock_light_client_contract, Contract, Contracts,
-    DeployedContracts,
+    BytecodeLimits, Confirmation, Contract, DeployedContracts, Deployer, Finality,
+    MAX_INIT_CODE_SIZE, MAX_RUNTIME_CODE_SIZE,
+};
+use sequencer_utils::provider::{connect_l1, DEFAULT_WS_RECONNECTS};
+use serde::{de::IgnoredAny, Deserialize};
+use std::{
+    fs::{self, File},
+    io::{stdin, stdout, IsTerminal},
+    path::{Path, PathBuf},
 };
-use std::{fs::File, io::stdout, path::PathBuf};
 use url::Url;
 
 /// Deploy contracts needed to run the sequencer.
@@ -32,9 +34,22 @@ use url::Url;
 /// addresses will be used in place of deploying a new contract wherever that contract is required
 /// in the deployment process. The generated .env file will include all the addresses passed in as
 /// well as those newly deployed.
+///
+/// Settings may also be read from a TOML file passed with --config. Options given on the command
+/// line or through the environment take precedence over the config file.
 #[derive(Clone, Debug, Parser)]
 struct Options {
+    /// Read deployment settings from a TOML config file.
+    ///
+    /// The file may set any option except MNEMONIC, which must not be stored in plain text. Keys
+    /// are the long option names with dashes replaced by underscores, plus `[light_client]` and
+    /// `[contracts]` tables for the light client parameters and predeployed contracts.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_CONFIG_PATH")]
+    config: Option<PathBuf>,
+
     /// A JSON-RPC endpoint for the L1 to deploy to.
+    ///
+    /// Both HTTP and WebSocket (`ws://`, `wss://`) endpoints are supported.
     #[clap(
         short,
         long,
@@ -43,6 +58,12 @@ struct Options {
     )]
     rpc_url: Url,
 
+    /// Number of times to re-establish a dropped WebSocket connection to the L1.
+    ///
+    /// Only used if RPC_URL is a WebSocket endpoint.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_WS_RECONNECTS", default_value_t = DEFAULT_WS_RECONNECTS)]
+    ws_reconnects: usize,
+
     /// URL of the HotShot orchestrator.
     ///
     /// This is used to get the stake table for initializing the light client contract.
@@ -79,6 +100,23 @@ struct Options {
     #[clap(short, long, name = "OUT", env = "ESPRESSO_DEPLOYER_OUT_PATH")]
     out: Option<PathBuf>,
 
+    /// Write a markdown report summarizing this deployment to REPORT.
+    ///
+    /// The report lists deployed and reused contracts, their versions and owners, deployment
+    /// transactions, gas spent and any steps which still need to be completed by hand.
+    #[clap(long, name = "REPORT", env = "ESPRESSO_DEPLOYER_REPORT_PATH")]
+    report: Option<PathBuf>,
+
+    /// Write a JSON summary of the gas spent by this deployment to GAS_REPORT.
+    ///
+    /// A summary table is always logged at the end of the run.
+    #[clap(long, name = "GAS_REPORT", env = "ESPRESSO_DEPLOYER_GAS_REPORT_PATH")]
+    gas_report: Option<PathBuf>,
+
+    /// Base URL of a block explorer for the L1, used to link transactions in the report.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_EXPLORER_URL")]
+    explorer_url: Option<Url>,
+
     #[clap(flatten)]
     contracts: DeployedContracts,
 
@@ -89,6 +127,204 @@ struct Options {
     /// Stake table capacity for the prover circuit
     #[clap(short, long, env = "ESPRESSO_SEQUENCER_STAKE_TABLE_CAPACITY", default_value_t = STAKE_TABLE_CAPACITY)]
     pub stake_table_capacity: usize,
+
+    /// Number of blocks per epoch for the light client contract.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_BLOCKS_PER_EPOCH", default_value_t = u32::MAX)]
+    pub blocks_per_epoch: u32,
+
+    /// Owner of the light client contract.
+    ///
+    /// If not provided, the deployer account becomes the owner.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_LIGHT_CLIENT_OWNER")]
+    pub light_client_owner: Option<Address>,
+
+    /// Maximum size in bytes of deployed contract code on the L1.
+    ///
+    /// Defaults to the EIP-170 limit. Contracts which exceed it are rejected before any
+    /// transactions are sent.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_MAX_RUNTIME_CODE_SIZE", default_value_t = MAX_RUNTIME_CODE_SIZE)]
+    pub max_runtime_code_size: usize,
+
+    /// Maximum size in bytes of contract init code on the L1.
+    ///
+    /// Defaults to the EIP-3860 limit. Contracts which exceed it are rejected before any
+    /// transactions are sent.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_MAX_INIT_CODE_SIZE", default_value_t = MAX_INIT_CODE_SIZE)]
+    pub max_init_code_size: usize,
+
+    /// After deploying, transfer ownership of all owned contracts to this address.
+    ///
+    /// This applies to predeployed contracts as well as newly deployed ones, so it can be used to
+    /// hand an existing deployment over to a timelock or multisig.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_TRANSFER_OWNERSHIP_TO")]
+    pub transfer_ownership_to: Option<Address>,
+
+    /// Only print the ownership transfers which would be performed by --transfer-ownership-to.
+    #[clap(long, requires = "transfer_ownership_to")]
+    pub transfer_ownership_dry_run: bool,
+
+    /// How final each deploy transaction must be before the contract is recorded as deployed.
+    ///
+    /// Either a number of confirmations or `finalized`, to wait until the block containing the
+    /// transaction is finalized. The default is `finalized` on Ethereum mainnet and 1 confirmation
+    /// on other chains.
+    #[clap(long, env = "ESPRESSO_DEPLOYER_FINALITY")]
+    pub finality: Option<Finality>,
+
+    /// Send transactions without asking for confirmation.
+    ///
+    /// When run from a terminal, the deployer prints each transaction it is about to send (the
+    /// contract, function, decoded arguments and estimated gas) and waits for confirmation. This
+    /// flag disables the prompts. They are also skipped when stdin is not a terminal.
+    #[clap(short, long)]
+    pub yes: bool,
+}
+
+impl Options {
+    /// Parse options from the command line and environment, falling back to the config file.
+    fn load() -> anyhow::Result<Self> {
+        let matches = Self::command().get_matches();
+        let mut opt = Self::from_arg_matches(&matches)?;
+        if let Some(path) = opt.config.clone() {
+            let config = ConfigFile::load(&path)?;
+            opt.merge(config, &matches);
+        }
+        opt.validate()?;
+        Ok(opt)
+    }
+
+    /// Take settings from `config` which were not given explicitly in `matches`.
+    fn merge(&mut self, config: ConfigFile, matches: &ArgMatches) {
+        // Options with a default value are only overridden if the user did not set them.
+        let explicit = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+        macro_rules! merge {
+            ($field:ident, $id:literal) => {
+                if let Some(value) = config.$field {
+                    if !explicit($id) {
+                        self.$field = value;
+                    }
+                }
+            };
+        }
+        merge!(rpc_url, "rpc_url");
+        merge!(ws_reconnects, "ws_reconnects");
+        merge!(orchestrator_url, "orchestrator_url");
+        merge!(account_index, "ACCOUNT_INDEX");
+        merge!(use_mock_contract, "use_mock_contract");
+        merge!(stake_table_capacity, "stake_table_capacity");
+        merge!(max_runtime_code_size, "max_runtime_code_size");
+        merge!(max_init_code_size, "max_init_code_size");
+        merge!(transfer_ownership_dry_run, "transfer_ownership_dry_run");
+        merge!(yes, "yes");
+        if let Some(blocks_per_epoch) = config.light_client.blocks_per_epoch {
+            if !explicit("blocks_per_epoch") {
+                self.blocks_per_epoch = blocks_per_epoch;
+            }
+        }
+
+        // Optional settings are taken from the config only if unset.
+        self.out = self.out.take().or(config.out);
+        self.report = self.report.take().or(config.report);
+        self.gas_report = self.gas_report.take().or(config.gas_report);
+        self.explorer_url = self.explorer_url.take().or(config.explorer_url);
+        self.light_client_owner = self.light_client_owner.or(config.light_client.owner);
+        self.transfer_ownership_to = self.transfer_ownership_to.or(config.transfer_ownership_to);
+        self.finality = self.finality.or(config.finality);
+        self.contracts = std::mem::take(&mut self.contracts).or(config.contracts);
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            self.stake_table_capacity > 0,
+            "stake table capacity must be positive"
+        );
+        ensure!(
+            self.blocks_per_epoch > 0,
+            "blocks per epoch must be positive"
+        );
+        ensure!(
+            self.light_client_owner != Some(Address::zero()),
+            "light client owner must not be the zero address"
+        );
+        // Clap only checks this for the command line, but the config file may set either option.
+        ensure!(
+            !self.transfer_ownership_dry_run || self.transfer_ownership_to.is_some(),
+            "an ownership transfer dry run requires a new owner (transfer_ownership_to)"
+        );
+        Ok(())
+    }
+}
+
+/// Deployment settings loaded from a TOML file.
+///
+/// Every key is optional, for example:
+///
+/// ```toml
+/// rpc_url = "https://sepolia.example.com"
+/// orchestrator_url = "http://orchestrator:40001"
+/// stake_table_capacity = 200
+/// finality = "finalized"
+///
+/// [light_client]
+/// blocks_per_epoch = 1000
+/// owner = "0x00000000000000000000000000000000000000aa"
+///
+/// [contracts]
+/// plonk_verifier = "0x00000000000000000000000000000000000000bb"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    rpc_url: Option<Url>,
+    ws_reconnects: Option<usize>,
+    orchestrator_url: Option<Url>,
+    account_index: Option<u32>,
+    out: Option<PathBuf>,
+    report: Option<PathBuf>,
+    gas_report: Option<PathBuf>,
+    explorer_url: Option<Url>,
+    use_mock_contract: Option<bool>,
+    stake_table_capacity: Option<usize>,
+    finality: Option<Finality>,
+    max_runtime_code_size: Option<usize>,
+    max_init_code_size: Option<usize>,
+    transfer_ownership_to: Option<Address>,
+    transfer_ownership_dry_run: Option<bool>,
+    yes: Option<bool>,
+    #[serde(default)]
+    light_client: LightClientConfig,
+    #[serde(default)]
+    contracts: DeployedContracts,
+    /// Only recognized in order to reject it with a helpful error.
+    mnemonic: Option<IgnoredAny>,
+}
+
+/// Parameters for initializing the light client contract.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LightClientConfig {
+    blocks_per_epoch: Option<u32>,
+    owner: Option<Address>,
+}
+
+impl ConfigFile {
+    fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("reading config file {}", path.display()))?;
+        let config: Self = toml::from_str(&text)
+            .with_context(|| format!("invalid config file {}", path.display()))?;
+        ensure!(
+            config.mnemonic.is_none(),
+            "config file {} must not contain the mnemonic, use ESPRESSO_SEQUENCER_ETH_MNEMONIC",
+            path.display()
+        );
+        Ok(config)
+    }
 }
 
 #[async_std::main]
@@ -96,10 +332,19 @@ async fn main() -> anyhow::Result<()> {
     setup_logging();
     setup_backtrace();
 
-    let opt = Options::parse();
-    let mut contracts = Contracts::from(opt.contracts);
+    let opt = Options::load()?;
+    let bytecode_limits = BytecodeLimits {
+        max_runtime_code_size: opt.max_runtime_code_size,
+        max_init_code_size: opt.max_init_code_size,
+    };
+    bytecode_limits.check_all(opt.use_mock_contract)?;
+    let confirmation = if opt.yes || !stdin().is_terminal() {
+        Confirmation::Auto
+    } else {
+        Confirmation::Interactive
+    };
 
-    let provider = Provider::<Http>::try_from(opt.rpc_url.to_string())?;
+    let provider = connect_l1(&opt.rpc_url, opt.ws_reconnects).await?;
     let chain_id = provider.get_chainid().await?.as_u64();
     let wallet = MnemonicBuilder::<English>::default()
         .phrase(opt.mnemonic.as_str())
@@ -107,42 +352,45 @@ async fn main() -> anyhow::Result<()> {
         .build()?
         .with_chain_id(chain_id);
     let owner = wallet.address();
+    let finality = opt
+        .finality
+        .unwrap_or_else(|| Finality::default_for_chain(chain_id));
+    let light_client_owner = opt.light_client_owner.unwrap_or(owner);
     let l1 = Arc::new(SignerMiddleware::new(provider, wallet));
+    if confirmation == Confirmation::Interactive {
+        eprintln!("Deploying to chain {chain_id} from {owner:#x}");
+    }
 
-    contracts
-        .deploy_tx(Contract::HotShot, HotShot::deploy(l1.clone(), ())?)
+    let mut deployer = Deployer::builder(l1)
+        .predeployed(opt.contracts.clone())
+        .bytecode_limits(bytecode_limits)
+        .confirmation(confirmation)
+        .finality(finality)
+        .build()
         .await?;
 
+    deployer.deploy_hotshot().await?;
+
     if opt.use_mock_contract {
         // LightClientMock is a non-upgradable contract, thus directly initialize
         // it via its constructor
-        contracts
-            .deploy_fn(Contract::LightClient, |contracts| {
-                deploy_mock_light_client_contract(l1.clone(), contracts, None).boxed()
-            })
-            .await?;
+        deployer.deploy_mock_light_client(None).await?;
     } else {
         // LightClient is a upgradable contract, thus deploy first,
         // then initialize it through a proxy contract
-        let lc_address = contracts
-            .deploy_fn(Contract::LightClient, |contracts| {
-                deploy_light_client_contract(l1.clone(), contracts).boxed()
-            })
+        let genesis = light_client_genesis(&opt.orchestrator_url, opt.stake_table_capacity).await?;
+        deployer
+            .deploy_light_client(genesis.into(), opt.blocks_per_epoch, light_client_owner)
             .await?;
-        let light_client = LightClient::new(lc_address, l1.clone());
+    }
 
-        let genesis = light_client_genesis(&opt.orchestrator_url, opt.stake_table_capacity).await?;
-        let data = light_client
-            .initialize(genesis.into(), u32::MAX, owner)
-            .calldata()
-            .context("calldata for initialize transaction not available")?;
-        contracts
-            .deploy_tx(
-                Contract::LightClientProxy,
-                ERC1967Proxy::deploy(l1.clone(), (lc_address, data))?,
-            )
+    if let Some(new_owner) = opt.transfer_ownership_to {
+        // The planned transfers are logged, which is all a dry run does.
+        deployer
+            .transfer_ownership(new_owner, opt.transfer_ownership_dry_run)
             .await?;
     }
+    let contracts = deployer.contracts();
 
     if let Some(out) = &opt.out {
         let file = File::options()
@@ -155,5 +403,34 @@ async fn main() -> anyhow::Result<()> {
         contracts.write(stdout())?;
     }
 
+    let mut report = deployer.report(owner).await?;
+    if let Some(path) = &opt.report {
+        if let Some(url) = opt.explorer_url {
+            report = report.with_explorer(url);
+        }
+        if let Some(proxy) = contracts.address(Contract::LightClientProxy) {
+            report.manual_step(format!(
+                "Configure the state prover with ESPRESSO_SEQUENCER_LIGHTCLIENT_ADDRESS={proxy:#x}"
+            ));
+        }
+        let file = File::options()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        report.write(file)?;
+    }
+
+    let gas = report.gas_report();
+    tracing::info!("gas usage:\n{gas}");
+    if let Some(path) = &opt.gas_report {
+        let file = File::options()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(path)?;
+        serde_json::to_writer_pretty(file, &gas)?;
+    }
+
     Ok(())
 }
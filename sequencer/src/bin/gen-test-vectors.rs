@@ -0,0 +1,34 @@
+//! Utility program to generate test vectors for the sequencer's Merkle trees.
+
+use clap::Parser;
+use sequencer::test_vectors::{TestVectors, DEFAULT_NUM_ENTRIES};
+use std::{fs::File, io::stdout, path::PathBuf};
+
+/// Generate language-independent test vectors for the fee and block Merkle trees.
+///
+/// The vectors contain the leaves, roots and proofs of deterministically generated trees, so that
+/// implementations in other languages can check compatibility with this crate's hashing and
+/// serialization. The vectors are written as JSON.
+#[derive(Clone, Debug, Parser)]
+struct Options {
+    /// Number of leaves to insert into each tree.
+    #[clap(short, long, default_value_t = DEFAULT_NUM_ENTRIES)]
+    num_entries: u64,
+
+    /// Write the test vectors to OUT instead of stdout.
+    #[clap(short, long, name = "OUT")]
+    out: Option<PathBuf>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opt = Options::parse();
+    let vectors = TestVectors::generate(opt.num_entries)?;
+    // Sanity check the vectors before handing them out.
+    vectors.verify()?;
+
+    match opt.out {
+        Some(path) => serde_json::to_writer_pretty(File::create(path)?, &vectors)?,
+        None => serde_json::to_writer_pretty(stdout(), &vectors)?,
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! Utility program to print the implementation history of an upgradable contract.
+
+use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
+use async_std::sync::Arc;
+use clap::Parser;
+use ethers::prelude::*;
+use sequencer_utils::{
+    deployer::implementation_history,
+    provider::{connect_l1, DEFAULT_WS_RECONNECTS},
+};
+use std::io::{stdout, Write};
+use url::Url;
+
+/// Print the implementation history of an ERC1967 proxy.
+///
+/// Every implementation the proxy has pointed to is listed, oldest first, with the block and
+/// transaction of the upgrade and the implementation's version. The output can be used to audit
+/// the upgrade trail of a deployment.
+#[derive(Clone, Debug, Parser)]
+struct Options {
+    /// A JSON-RPC endpoint for the L1.
+    ///
+    /// Both HTTP and WebSocket (`ws://`, `wss://`) endpoints are supported.
+    #[clap(
+        short,
+        long,
+        env = "ESPRESSO_SEQUENCER_L1_PROVIDER",
+        default_value = "http://localhost:8545"
+    )]
+    rpc_url: Url,
+
+    /// Scan for upgrades starting from block FROM.
+    ///
+    /// This should be at or before the block in which the proxy was deployed.
+    #[clap(long, name = "FROM", default_value = "0")]
+    from: u64,
+
+    /// Print the history as JSON instead of a markdown table.
+    #[clap(long)]
+    json: bool,
+
+    /// Address of the proxy contract.
+    proxy: Address,
+}
+
+#[async_std::main]
+async fn main() -> anyhow::Result<()> {
+    setup_logging();
+    setup_backtrace();
+
+    let opt = Options::parse();
+    let l1 = Arc::new(connect_l1(&opt.rpc_url, DEFAULT_WS_RECONNECTS).await?);
+    let history = implementation_history(l1, opt.proxy, opt.from).await?;
+
+    let mut out = stdout();
+    if opt.json {
+        serde_json::to_writer_pretty(&mut out, &history)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "| # | Implementation | Version | Block | Transaction |"
+    )?;
+    writeln!(out, "|---|---|---|---|---|")?;
+    for (i, record) in history.iter().enumerate() {
+        let version = match record.version {
+            Some((major, minor, patch)) => format!("{major}.{minor}.{patch}"),
+            None => "-".into(),
+        };
+        writeln!(
+            out,
+            "| {i} | `{:#x}` | {version} | {} | `{:#x}` |",
+            record.implementation, record.block, record.tx_hash
+        )?;
+    }
+    Ok(())
+}
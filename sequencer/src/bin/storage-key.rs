@@ -0,0 +1,55 @@
+use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
+use clap::Parser;
+use sequencer::persistence::{
+    self, encryption::Encryption, PersistenceOptions, SequencerPersistence,
+};
+use std::{fs, path::PathBuf};
+
+/// Manage the key used to encrypt private data in consensus storage.
+///
+/// To rotate the key, generate a new key, then run `rotate-fs` or `rotate-sql` with the new key as
+/// ESPRESSO_SEQUENCER_STORAGE_ENCRYPTION_KEY_FILE and the old key as
+/// ESPRESSO_SEQUENCER_STORAGE_PREVIOUS_ENCRYPTION_KEY_FILE. Do not run rotation while the
+/// sequencer is running.
+#[derive(Clone, Debug, Parser)]
+enum Options {
+    /// Generate a new random storage encryption key.
+    Generate {
+        /// Write the key to OUT instead of stdout.
+        #[clap(short, long, name = "OUT")]
+        out: Option<PathBuf>,
+    },
+    /// Re-encrypt file system storage under the current key.
+    RotateFs(persistence::fs::Options),
+    /// Re-encrypt SQL storage under the current key.
+    RotateSql(persistence::sql::Options),
+}
+
+#[async_std::main]
+async fn main() -> anyhow::Result<()> {
+    setup_logging();
+    setup_backtrace();
+
+    match Options::parse() {
+        Options::Generate { out } => {
+            let key = Encryption::generate_key();
+            match out {
+                Some(path) => fs::write(path, key)?,
+                None => println!("{key}"),
+            }
+            Ok(())
+        }
+        Options::RotateFs(opt) => rotate(opt).await,
+        Options::RotateSql(opt) => rotate(opt).await,
+    }
+}
+
+async fn rotate(opt: impl PersistenceOptions) -> anyhow::Result<()> {
+    let mut storage = opt.create().await?;
+    if storage.reseal_config().await? {
+        tracing::info!("re-encrypted network config");
+    } else {
+        tracing::info!("network config is missing or already encrypted under the current key");
+    }
+    Ok(())
+}
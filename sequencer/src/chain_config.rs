@@ -1,10 +1,11 @@
-use crate::state::FeeAmount;
+use crate::{state::FeeAmount, NamespaceId, Transaction};
 use committable::{Commitment, Committable};
 use derive_more::{From, Into};
 use ethers::types::U256;
 use itertools::Either;
 use sequencer_utils::impl_to_fixed_bytes;
 use serde::{Deserialize, Serialize};
+use snafu::Snafu;
 
 #[derive(Default, Hash, Copy, Clone, Debug, Deserialize, Serialize, PartialEq, Eq, From, Into)]
 pub struct ChainId(U256);
@@ -53,6 +54,69 @@ impl ChainConfig {
     pub fn max_block_size(&self) -> u64 {
         self.max_block_size
     }
+
+    /// Check that `tx` can fit in a block under this config.
+    ///
+    /// Only the transaction payload is counted, so a transaction which passes this check may still
+    /// not fit once the namespace and transaction table overhead of a block is accounted for.
+    pub fn check_transaction(&self, tx: &Transaction) -> Result<(), SizeLimitError> {
+        let tx_size = tx.payload().len() as u64;
+        if tx_size > self.max_block_size {
+            return Err(SizeLimitError::TransactionTooLarge {
+                namespace: tx.namespace(),
+                tx_size,
+                max_block_size: self.max_block_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Check that a block payload of `block_size` bytes is allowed under this config.
+    ///
+    /// Consensus requires block payloads to be strictly smaller than the maximum block size, so a
+    /// payload of exactly `max_block_size` bytes is rejected.
+    pub fn check_block_size(&self, block_size: u64) -> Result<(), SizeLimitError> {
+        if block_size >= self.max_block_size {
+            return Err(SizeLimitError::BlockTooLarge {
+                block_size,
+                max_block_size: self.max_block_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A chain config which the network has activated, along with the first block it applied to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainConfigActivation {
+    /// Height of the first block produced under this config.
+    pub height: u64,
+    /// View of the first block produced under this config.
+    pub view: u64,
+    pub config: ChainConfig,
+}
+
+/// Explanation of why a transaction or block exceeds the limits of a [`ChainConfig`].
+///
+/// Errors include the offending size along with the limit in effect, so that clients can tell
+/// exactly how far over the limit they are.
+#[derive(Clone, Debug, Snafu, Deserialize, Serialize, PartialEq, Eq)]
+pub enum SizeLimitError {
+    #[snafu(display(
+        "transaction of {tx_size} bytes in namespace {namespace} exceeds the maximum block size of {max_block_size} bytes"
+    ))]
+    TransactionTooLarge {
+        namespace: NamespaceId,
+        tx_size: u64,
+        max_block_size: u64,
+    },
+    #[snafu(display(
+        "block payload of {block_size} bytes must be smaller than the maximum block size of {max_block_size} bytes"
+    ))]
+    BlockTooLarge {
+        block_size: u64,
+        max_block_size: u64,
+    },
 }
 
 impl Committable for ChainConfig {
@@ -122,6 +186,34 @@ mod tests {
         assert!(chain_config != other_config);
     }
 
+    #[test]
+    fn test_chain_config_size_limits() {
+        let chain_config = ChainConfig::new(35353, 10, 0);
+
+        let tx = Transaction::new(NamespaceId::from(1), vec![0; 10]);
+        chain_config.check_transaction(&tx).unwrap();
+        let tx = Transaction::new(NamespaceId::from(1), vec![0; 11]);
+        assert_eq!(
+            chain_config.check_transaction(&tx).unwrap_err(),
+            SizeLimitError::TransactionTooLarge {
+                namespace: NamespaceId::from(1),
+                tx_size: 11,
+                max_block_size: 10,
+            }
+        );
+
+        chain_config.check_block_size(9).unwrap();
+        for block_size in [10, 11] {
+            assert_eq!(
+                chain_config.check_block_size(block_size).unwrap_err(),
+                SizeLimitError::BlockTooLarge {
+                    block_size,
+                    max_block_size: 10,
+                }
+            );
+        }
+    }
+
     #[test]
     fn test_resolve_chain_config() {
         let chain_config = ChainConfig::default();
@@ -24,7 +24,8 @@ use vbs::version::StaticVersionType;
 
 use crate::{
     network, persistence::SequencerPersistence, state_signature::StateSigner,
-    static_stake_table_commitment, ElectionConfig, Node, NodeState, PubKey, SeqTypes, Transaction,
+    static_stake_table_commitment, tx_trace::TxTracer, ElectionConfig, Node, NodeState, PubKey,
+    SeqTypes, Transaction,
 };
 use hotshot_events_service::events_source::{EventConsumer, EventsStreamer};
 /// The consensus handle
@@ -49,6 +50,13 @@ pub struct SequencerContext<
     /// Context for generating state signatures.
     state_signer: Arc<StateSigner<Ver>>,
 
+    /// Persistent consensus storage, shared with the event handler.
+    #[derivative(Debug = "ignore")]
+    persistence: Arc<RwLock<P>>,
+
+    /// Traces of transactions recently submitted through this node.
+    tx_tracer: Arc<TxTracer>,
+
     /// An orchestrator to wait for before starting consensus.
     #[derivative(Debug = "ignore")]
     wait_for_orchestrator: Option<Arc<OrchestratorClient>>,
@@ -168,6 +176,8 @@ impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static
             handle,
             node_index,
             state_signer: Arc::new(state_signer),
+            persistence: persistence.clone(),
+            tx_tracer: Default::default(),
             tasks: Default::default(),
             detached: false,
             wait_for_orchestrator: None,
@@ -180,6 +190,7 @@ impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static
                 events,
                 persistence,
                 ctx.state_signer.clone(),
+                ctx.tx_tracer.clone(),
                 Some(event_streamer.clone()),
             ),
         );
@@ -204,6 +215,16 @@ impl<N: network::Type, P: SequencerPersistence, Ver: StaticVersionType + 'static
         self.state_signer.clone()
     }
 
+    /// Return a reference to the persistent consensus storage.
+    pub fn persistence(&self) -> Arc<RwLock<P>> {
+        self.persistence.clone()
+    }
+
+    /// Return a reference to the transaction tracer.
+    pub fn tx_tracer(&self) -> Arc<TxTracer> {
+        self.tx_tracer.clone()
+    }
+
     /// Stream consensus events.
     pub fn get_event_stream(&self) -> impl Stream<Item = Event<SeqTypes>> {
         self.handle.get_event_stream()
@@ -289,6 +310,7 @@ async fn handle_events<Ver: StaticVersionType>(
     mut events: impl Stream<Item = Event<SeqTypes>> + Unpin,
     persistence: Arc<RwLock<impl SequencerPersistence>>,
     state_signer: Arc<StateSigner<Ver>>,
+    tx_tracer: Arc<TxTracer>,
     events_streamer: Option<Arc<RwLock<EventsStreamer<SeqTypes>>>>,
 ) {
     while let Some(event) = events.next().await {
@@ -302,6 +324,9 @@ async fn handle_events<Ver: StaticVersionType>(
         // Generate state signature.
         state_signer.handle_event(&event).await;
 
+        // Follow the progress of traced transactions.
+        tx_tracer.handle_event(&event).await;
+
         // Send the event via the event streaming service
         if let Some(events_streamer) = events_streamer.as_ref() {
             events_streamer.write().await.handle_event(event).await;
@@ -211,6 +211,7 @@ mod test {
     use crate::NodeState;
     use contract_bindings::fee_contract::FeeContract;
     use ethers::utils::{parse_ether, Anvil};
+    use sequencer_utils::deployer::deposit_fee;
 
     #[async_std::test]
     async fn test_l1_block_fetching() -> anyhow::Result<()> {
@@ -307,15 +308,16 @@ mod test {
         for n in 1..=deposits {
             // Varied amounts are less boring.
             let amount = n as f32 / 10.0;
-            let receipt = fee_contract_proxy
-                .deposit(wallet_address)
-                .value(parse_ether(amount).unwrap())
-                .send()
-                .await?
-                .await?;
+            let receipt = deposit_fee(
+                client.clone(),
+                fee_contract_proxy.address(),
+                parse_ether(amount).unwrap(),
+                wallet_address,
+            )
+            .await?;
 
             // Successful transactions have `status` of `1`.
-            assert_eq!(Some(U64::from(1)), receipt.clone().unwrap().status);
+            assert_eq!(Some(U64::from(1)), receipt.status);
         }
 
         let head = l1_client.get_block_number().await;
@@ -27,7 +27,9 @@ use url::Url;
 pub mod l1_client;
 pub mod persistence;
 pub mod state;
+pub mod test_vectors;
 pub mod transaction;
+pub mod tx_trace;
 
 use derivative::Derivative;
 use hotshot::{
@@ -76,7 +78,7 @@ use std::time::Duration;
 use hotshot::traits::implementations::{CombinedNetworks, Libp2pNetwork};
 
 pub use block::payload::Payload;
-pub use chain_config::ChainConfig;
+pub use chain_config::{ChainConfig, ChainConfigActivation, SizeLimitError};
 pub use header::Header;
 pub use l1_client::L1BlockInfo;
 pub use options::Options;
@@ -195,6 +197,10 @@ impl NodeState {
         self
     }
 
+    pub fn chain_config(&self) -> &ChainConfig {
+        &self.chain_config
+    }
+
     fn l1_client(&self) -> &L1Client {
         &self.l1_client
     }
@@ -298,6 +304,13 @@ pub async fn init_node<P: SequencerPersistence, Ver: StaticVersionType + 'static
     let (config, wait_for_orchestrator) = match persistence.load_config().await? {
         Some(config) => {
             tracing::info!("loaded network config from storage, rejoining existing network");
+            // The node can run with the config as it is, so if it cannot be re-encrypted, it is
+            // left to be re-encrypted on the next restart.
+            match persistence.reseal_config().await {
+                Ok(true) => tracing::info!("re-encrypted network config under the current key"),
+                Ok(false) => {}
+                Err(err) => tracing::warn!("cannot re-encrypt network config: {err:#}"),
+            }
             (config, false)
         }
         None => {
@@ -9,7 +9,8 @@
 //! persistence which is _required_ to run a node.
 
 use crate::{
-    ElectionConfig, Header, Leaf, NodeState, PubKey, SeqTypes, ValidatedState, ViewNumber,
+    ChainConfigActivation, ElectionConfig, Header, Leaf, NodeState, PubKey, SeqTypes,
+    ValidatedState, ViewNumber,
 };
 use anyhow::{ensure, Context};
 use async_std::sync::Arc;
@@ -29,6 +30,7 @@ use hotshot_types::{
 };
 use std::cmp::max;
 
+pub mod encryption;
 pub mod fs;
 pub mod no_storage;
 pub mod sql;
@@ -54,6 +56,27 @@ pub trait SequencerPersistence: Send + Sync + 'static {
     /// Save the orchestrator config to storage.
     async fn save_config(&mut self, cfg: &NetworkConfig) -> anyhow::Result<()>;
 
+    /// Check whether the orchestrator config must be saved again to be stored under the current
+    /// encryption key.
+    ///
+    /// This is the case for a config stored in plaintext before storage encryption was enabled, or
+    /// under the previous key during a key rotation. Returns `false` if no config exists.
+    async fn config_needs_reseal(&self) -> anyhow::Result<bool>;
+
+    /// Save the orchestrator config again if it is not stored under the current encryption key.
+    ///
+    /// Returns whether the config was saved again.
+    async fn reseal_config(&mut self) -> anyhow::Result<bool> {
+        if !self.config_needs_reseal().await? {
+            return Ok(false);
+        }
+        let Some(config) = self.load_config().await? else {
+            return Ok(false);
+        };
+        self.save_config(&config).await?;
+        Ok(true)
+    }
+
     async fn collect_garbage(&mut self, view: ViewNumber) -> anyhow::Result<()>;
 
     /// Saves the latest decided leaf.
@@ -85,6 +108,56 @@ pub trait SequencerPersistence: Send + Sync + 'static {
     /// Load the validated state after `header`, if available.
     async fn load_validated_state(&self, header: &Header) -> anyhow::Result<ValidatedState>;
 
+    /// Load every chain config this node has seen activated, ordered by activation height.
+    async fn load_chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>>;
+
+    /// Load the most recently activated chain config in the history, if any.
+    async fn load_latest_chain_config(&self) -> anyhow::Result<Option<ChainConfigActivation>>;
+
+    /// Append a new entry to the chain config history.
+    ///
+    /// Use [`record_chain_config`](Self::record_chain_config) instead, which only appends configs
+    /// that differ from the latest one.
+    async fn append_chain_config(
+        &mut self,
+        activation: &ChainConfigActivation,
+    ) -> anyhow::Result<()>;
+
+    /// Record the chain config used by the decided `leaf` in the chain config history.
+    ///
+    /// A new entry is added only if the config differs from the latest recorded config, so each
+    /// entry is a config along with the first block this node saw decided under that config.
+    async fn record_chain_config(&mut self, leaf: &Leaf) -> anyhow::Result<()> {
+        let header = leaf.get_block_header();
+        if let Some(latest) = self.load_latest_chain_config().await? {
+            if latest.config.commit() == header.chain_config.commit()
+                || latest.height >= header.height
+            {
+                return Ok(());
+            }
+        }
+        let Some(config) = header.chain_config.resolve() else {
+            tracing::warn!(
+                height = header.height,
+                commit = %header.chain_config.commit(),
+                "chain config changed but header only contains a commitment, not recording it",
+            );
+            return Ok(());
+        };
+
+        tracing::info!(
+            height = header.height,
+            ?config,
+            "new chain config activated"
+        );
+        self.append_chain_config(&ChainConfigActivation {
+            height: header.height,
+            view: leaf.get_view_number().get_u64(),
+            config,
+        })
+        .await
+    }
+
     /// Load the latest known consensus state.
     ///
     /// Returns an initializer to resume HotShot from the latest saved state (or start from genesis,
@@ -188,6 +261,17 @@ pub trait SequencerPersistence: Send + Sync + 'static {
                     tracing::error!("Failed to garbage collect. {err:#}",);
                 }
             }
+
+            // Process leaves oldest first, so that each config is recorded with the first block
+            // which used it.
+            for LeafInfo { leaf, .. } in leaf_chain.iter().rev() {
+                if let Err(err) = self.record_chain_config(leaf).await {
+                    tracing::error!(
+                        height = leaf.get_block_header().height,
+                        "Failed to record chain config. {err:#}"
+                    );
+                }
+            }
         }
     }
 
@@ -208,14 +292,21 @@ pub trait SequencerPersistence: Send + Sync + 'static {
 
 #[cfg(test)]
 mod testing {
-    use super::*;
+    use super::{encryption::Encryption, *};
 
     #[async_trait]
     pub trait TestablePersistence: SequencerPersistence {
         type Storage;
 
         async fn tmp_storage() -> Self::Storage;
-        async fn connect(storage: &Self::Storage) -> Self;
+
+        /// Connect to `storage`, encrypting private data with `encryption`, if any.
+        async fn connect_encrypted(storage: &Self::Storage, encryption: Option<Encryption>)
+            -> Self;
+
+        async fn connect(storage: &Self::Storage) -> Self {
+            Self::connect_encrypted(storage, None).await
+        }
     }
 }
 
@@ -224,9 +315,10 @@ mod testing {
 mod persistence_tests {
 
     use super::*;
-    use crate::{NodeState, Transaction};
+    use crate::{ChainConfig, NodeState, Transaction};
     use async_compatibility_layer::logging::{setup_backtrace, setup_logging};
 
+    use super::encryption::Encryption;
     use hotshot::types::SignatureKey;
     use hotshot::{traits::BlockPayload, types::BLSPubKey};
     use hotshot_types::{event::HotShotAction, vid::vid_scheme};
@@ -453,4 +545,117 @@ mod persistence_tests {
             Some(vid_share3)
         );
     }
+
+    #[async_std::test]
+    pub async fn test_chain_config_history<P: TestablePersistence>() {
+        setup_logging();
+        setup_backtrace();
+
+        let tmp = P::tmp_storage().await;
+        let mut storage = P::connect(&tmp).await;
+
+        // Initially, there is no history.
+        assert_eq!(storage.load_chain_config_history().await.unwrap(), []);
+        assert_eq!(storage.load_latest_chain_config().await.unwrap(), None);
+
+        // The first decided leaf activates its config.
+        let mut leaf = Leaf::genesis(&NodeState::mock());
+        let config1 = leaf.get_block_header().chain_config.resolve().unwrap();
+        storage.record_chain_config(&leaf).await.unwrap();
+        let activation1 = ChainConfigActivation {
+            height: 0,
+            view: 0,
+            config: config1,
+        };
+        assert_eq!(
+            storage.load_chain_config_history().await.unwrap(),
+            [activation1]
+        );
+
+        // Later leaves with the same config do not change the history.
+        leaf.get_block_header_mut().height = 1;
+        storage.record_chain_config(&leaf).await.unwrap();
+        assert_eq!(
+            storage.load_chain_config_history().await.unwrap(),
+            [activation1]
+        );
+
+        // A leaf with a new config is recorded.
+        let config2 = ChainConfig::new(1u16, 2 * config1.max_block_size(), 0);
+        leaf.get_block_header_mut().height = 2;
+        leaf.get_block_header_mut().chain_config = config2.into();
+        storage.record_chain_config(&leaf).await.unwrap();
+        let activation2 = ChainConfigActivation {
+            height: 2,
+            view: 0,
+            config: config2,
+        };
+        assert_eq!(
+            storage.load_chain_config_history().await.unwrap(),
+            [activation1, activation2]
+        );
+        assert_eq!(
+            storage.load_latest_chain_config().await.unwrap(),
+            Some(activation2)
+        );
+
+        // Old leaves are ignored, even if their config differs from the latest one.
+        leaf.get_block_header_mut().height = 1;
+        leaf.get_block_header_mut().chain_config = config1.into();
+        storage.record_chain_config(&leaf).await.unwrap();
+        assert_eq!(
+            storage.load_chain_config_history().await.unwrap(),
+            [activation1, activation2]
+        );
+    }
+
+    #[async_std::test]
+    pub async fn test_config_encryption<P: TestablePersistence>() {
+        setup_logging();
+        setup_backtrace();
+
+        let tmp = P::tmp_storage().await;
+        let config = NetworkConfig {
+            node_index: 3,
+            ..Default::default()
+        };
+        let load = |storage: P| async move {
+            let config = storage.load_config().await?.unwrap();
+            anyhow::Ok(serde_json::to_value(config).unwrap())
+        };
+        let expected = serde_json::to_value(&config).unwrap();
+
+        // Without a key, the config is stored in plaintext.
+        let mut storage = P::connect(&tmp).await;
+        storage.save_config(&config).await.unwrap();
+        assert!(!storage.reseal_config().await.unwrap());
+        assert_eq!(load(storage).await.unwrap(), expected);
+
+        // Once a key is configured, the plaintext config is still readable, and is encrypted.
+        let key = Encryption::new([1; 32]);
+        let mut storage = P::connect_encrypted(&tmp, Some(key.clone())).await;
+        assert!(storage.reseal_config().await.unwrap());
+        assert!(!storage.reseal_config().await.unwrap());
+        assert_eq!(load(storage).await.unwrap(), expected);
+
+        // The encrypted config cannot be read without the key, or with the wrong key.
+        load(P::connect(&tmp).await).await.unwrap_err();
+        let new_key = Encryption::new([2; 32]);
+        load(P::connect_encrypted(&tmp, Some(new_key.clone())).await)
+            .await
+            .unwrap_err();
+
+        // After a key rotation, the config is read with the previous key, and re-encrypted under
+        // the new key.
+        let rotated = new_key.clone().with_previous_key([1; 32]);
+        let mut storage = P::connect_encrypted(&tmp, Some(rotated)).await;
+        assert!(storage.reseal_config().await.unwrap());
+        assert_eq!(load(storage).await.unwrap(), expected);
+        load(P::connect_encrypted(&tmp, Some(key)).await)
+            .await
+            .unwrap_err();
+        let mut storage = P::connect_encrypted(&tmp, Some(new_key)).await;
+        assert!(!storage.reseal_config().await.unwrap());
+        assert_eq!(load(storage).await.unwrap(), expected);
+    }
 }
@@ -0,0 +1,281 @@
+//! At-rest encryption of private data in consensus storage.
+//!
+//! Most of what a node persists is public consensus data, but the network config saved on first
+//! startup includes the node's private staking and state keys. Operators who keep consensus storage
+//! on shared infrastructure can encrypt this data with a key kept outside of storage, either in a
+//! file or fetched from a key management service by running a command.
+//!
+//! Encrypted blobs are tagged with a short header, so data which was written in plaintext before
+//! encryption was enabled can still be read, and is encrypted the next time it is saved. To rotate
+//! the key, configure the new key along with the old one as the previous key, and either restart the
+//! node, which re-encrypts private data at startup, or run the `storage-key` binary.
+
+use anyhow::{bail, ensure, Context};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use clap::Parser;
+use derivative::Derivative;
+use ethers::utils::hex;
+use rand::RngCore;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// Header identifying an encrypted blob.
+const MAGIC: &[u8] = b"ESQENC01";
+
+/// Length in bytes of the nonce following the header.
+const NONCE_LEN: usize = 12;
+
+/// Options for encrypting private data in consensus storage.
+#[derive(Parser, Clone, Debug, Default)]
+pub struct Options {
+    /// File containing the hex-encoded 32-byte key used to encrypt private data in storage.
+    ///
+    /// If neither this nor ENCRYPTION_KEY_COMMAND is set, private data is stored in plaintext.
+    #[clap(
+        long,
+        env = "ESPRESSO_SEQUENCER_STORAGE_ENCRYPTION_KEY_FILE",
+        conflicts_with = "encryption_key_command"
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+
+    /// Shell command which prints the hex-encoded 32-byte storage encryption key.
+    ///
+    /// This can be used to fetch the key from a key management service at startup, so it never
+    /// touches the disk.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_STORAGE_ENCRYPTION_KEY_COMMAND")]
+    pub encryption_key_command: Option<String>,
+
+    /// File containing the previous storage encryption key, during a key rotation.
+    ///
+    /// Data encrypted under this key can still be read, and is re-encrypted under the current key
+    /// when it is next saved.
+    #[clap(long, env = "ESPRESSO_SEQUENCER_STORAGE_PREVIOUS_ENCRYPTION_KEY_FILE")]
+    pub previous_encryption_key_file: Option<PathBuf>,
+}
+
+impl Options {
+    /// Load the configured keys, if encryption is enabled.
+    pub fn load(&self) -> anyhow::Result<Option<Encryption>> {
+        let key = match (&self.encryption_key_file, &self.encryption_key_command) {
+            (Some(path), _) => read_key_file(path)?,
+            (None, Some(cmd)) => run_key_command(cmd)?,
+            (None, None) => {
+                ensure!(
+                    self.previous_encryption_key_file.is_none(),
+                    "previous storage encryption key given without a current key"
+                );
+                return Ok(None);
+            }
+        };
+        let previous = self
+            .previous_encryption_key_file
+            .as_deref()
+            .map(read_key_file)
+            .transpose()?;
+        Ok(Some(Encryption { key, previous }))
+    }
+}
+
+/// Keys for encrypting and decrypting private data in storage.
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct Encryption {
+    #[derivative(Debug = "ignore")]
+    key: Key,
+    #[derivative(Debug = "ignore")]
+    previous: Option<Key>,
+}
+
+impl Encryption {
+    /// Encrypt data with a single key.
+    pub fn new(key: [u8; 32]) -> Self {
+        Self {
+            key: key.into(),
+            previous: None,
+        }
+    }
+
+    /// Also accept data encrypted under `key` when decrypting.
+    pub fn with_previous_key(mut self, key: [u8; 32]) -> Self {
+        self.previous = Some(key.into());
+        self
+    }
+
+    /// Generate a random key, hex-encoded in the format expected in a key file.
+    pub fn generate_key() -> String {
+        let mut key = [0; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        hex::encode(key)
+    }
+
+    /// Encrypt `plaintext` under the current key.
+    pub fn encrypt(&self, plaintext: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut nonce = [0; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = ChaCha20Poly1305::new(&self.key)
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| anyhow::anyhow!("encryption failed"))?;
+
+        let mut bytes = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend(ciphertext);
+        Ok(bytes)
+    }
+
+    /// Decrypt `bytes` under the current or previous key.
+    ///
+    /// Plaintext (data without the encryption header) is returned as is. The second return value
+    /// indicates whether the data should be re-encrypted, because it was in plaintext or encrypted
+    /// under the previous key.
+    pub fn decrypt(&self, bytes: &[u8]) -> anyhow::Result<(Vec<u8>, bool)> {
+        let Some((nonce, ciphertext)) = split_encrypted(bytes)? else {
+            return Ok((bytes.to_vec(), true));
+        };
+        if let Ok(plaintext) = ChaCha20Poly1305::new(&self.key).decrypt(nonce, ciphertext) {
+            return Ok((plaintext, false));
+        }
+        if let Some(previous) = &self.previous {
+            if let Ok(plaintext) = ChaCha20Poly1305::new(previous).decrypt(nonce, ciphertext) {
+                return Ok((plaintext, true));
+            }
+        }
+        bail!("failed to decrypt storage, wrong encryption key?");
+    }
+}
+
+/// Whether `bytes` were produced by [`Encryption::encrypt`].
+pub fn is_encrypted(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Decrypt `bytes` read from storage, if they are encrypted.
+///
+/// Fails if the data is encrypted and no key is configured. The second return value indicates
+/// whether the data should be re-encrypted when encryption is enabled.
+pub fn open(encryption: Option<&Encryption>, bytes: Vec<u8>) -> anyhow::Result<(Vec<u8>, bool)> {
+    match encryption {
+        Some(encryption) => encryption.decrypt(&bytes),
+        None if is_encrypted(&bytes) => bail!(
+            "storage contains encrypted data, but no encryption key is configured; set \
+             ESPRESSO_SEQUENCER_STORAGE_ENCRYPTION_KEY_FILE"
+        ),
+        None => Ok((bytes, false)),
+    }
+}
+
+fn split_encrypted(bytes: &[u8]) -> anyhow::Result<Option<(&Nonce, &[u8])>> {
+    let Some(rest) = bytes.strip_prefix(MAGIC) else {
+        return Ok(None);
+    };
+    ensure!(rest.len() >= NONCE_LEN, "truncated encrypted data");
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    Ok(Some((Nonce::from_slice(nonce), ciphertext)))
+}
+
+fn parse_key(s: &str) -> anyhow::Result<Key> {
+    let bytes = hex::decode(s.trim()).context("storage encryption key is not valid hex")?;
+    let key: [u8; 32] = bytes.try_into().map_err(|bytes: Vec<u8>| {
+        anyhow::anyhow!(
+            "invalid storage encryption key length: {} (expected 32)",
+            bytes.len()
+        )
+    })?;
+    Ok(key.into())
+}
+
+fn read_key_file(path: &Path) -> anyhow::Result<Key> {
+    let s = fs::read_to_string(path)
+        .with_context(|| format!("reading storage encryption key from {}", path.display()))?;
+    parse_key(&s)
+}
+
+fn run_key_command(cmd: &str) -> anyhow::Result<Key> {
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .output()
+        .context("running storage encryption key command")?;
+    ensure!(
+        output.status.success(),
+        "storage encryption key command failed with {}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    parse_key(std::str::from_utf8(&output.stdout).context("key command output is not UTF-8")?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_encryption_roundtrip() {
+        let encryption = Encryption::new([1; 32]);
+        let bytes = encryption.encrypt(b"secret").unwrap();
+        assert!(is_encrypted(&bytes));
+        assert!(!bytes.windows(6).any(|w| w == b"secret"));
+        assert_eq!(
+            encryption.decrypt(&bytes).unwrap(),
+            (b"secret".to_vec(), false)
+        );
+
+        // Plaintext is readable, but flagged for re-encryption.
+        assert_eq!(
+            encryption.decrypt(b"public").unwrap(),
+            (b"public".to_vec(), true)
+        );
+
+        // Encrypted data cannot be read without the key.
+        Encryption::new([2; 32]).decrypt(&bytes).unwrap_err();
+        open(None, bytes.clone()).unwrap_err();
+
+        // After rotation, data encrypted under the old key is still readable, but flagged for
+        // re-encryption.
+        let rotated = Encryption::new([2; 32]).with_previous_key([1; 32]);
+        assert_eq!(rotated.decrypt(&bytes).unwrap(), (b"secret".to_vec(), true));
+        let bytes = rotated.encrypt(b"secret").unwrap();
+        assert_eq!(
+            rotated.decrypt(&bytes).unwrap(),
+            (b"secret".to_vec(), false)
+        );
+        encryption.decrypt(&bytes).unwrap_err();
+    }
+
+    #[test]
+    fn test_encryption_options() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("key");
+        fs::write(&path, Encryption::generate_key()).unwrap();
+
+        let opt = Options {
+            encryption_key_file: Some(path.clone()),
+            ..Default::default()
+        };
+        let from_file = opt.load().unwrap().unwrap();
+
+        let opt = Options {
+            encryption_key_command: Some(format!("cat {}", path.display())),
+            ..Default::default()
+        };
+        let from_command = opt.load().unwrap().unwrap();
+        let bytes = from_file.encrypt(b"secret").unwrap();
+        assert_eq!(from_command.decrypt(&bytes).unwrap().0, b"secret");
+
+        assert!(Options::default().load().unwrap().is_none());
+
+        fs::write(&path, "not a key").unwrap();
+        Options {
+            encryption_key_file: Some(path),
+            ..Default::default()
+        }
+        .load()
+        .unwrap_err();
+    }
+}
@@ -1,5 +1,8 @@
-use super::{NetworkConfig, PersistenceOptions, SequencerPersistence};
-use crate::{Header, Leaf, SeqTypes, ValidatedState, ViewNumber};
+use super::{
+    encryption::{self, Encryption},
+    NetworkConfig, PersistenceOptions, SequencerPersistence,
+};
+use crate::{ChainConfigActivation, Header, Leaf, SeqTypes, ValidatedState, ViewNumber};
 use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
 use clap::Parser;
@@ -24,6 +27,10 @@ pub struct Options {
     /// Storage path for persistent data.
     #[clap(long, env = "ESPRESSO_SEQUENCER_STORAGE_PATH")]
     pub path: PathBuf,
+
+    /// Encryption of private data.
+    #[clap(flatten)]
+    pub encryption: encryption::Options,
 }
 
 impl Default for Options {
@@ -37,7 +44,10 @@ impl PersistenceOptions for Options {
     type Persistence = Persistence;
 
     async fn create(self) -> anyhow::Result<Persistence> {
-        Ok(Persistence(self.path))
+        Ok(Persistence {
+            path: self.path,
+            encryption: self.encryption.load()?,
+        })
     }
 
     async fn reset(self) -> anyhow::Result<()> {
@@ -47,27 +57,55 @@ impl PersistenceOptions for Options {
 
 /// File system backed persistence.
 #[derive(Clone, Debug)]
-pub struct Persistence(PathBuf);
+pub struct Persistence {
+    path: PathBuf,
+    encryption: Option<Encryption>,
+}
 
 impl Persistence {
     fn config_path(&self) -> PathBuf {
-        self.0.join("hotshot.cfg")
+        self.path.join("hotshot.cfg")
     }
 
     fn voted_view_path(&self) -> PathBuf {
-        self.0.join("highest_voted_view")
+        self.path.join("highest_voted_view")
     }
 
     fn anchor_leaf_path(&self) -> PathBuf {
-        self.0.join("anchor_leaf")
+        self.path.join("anchor_leaf")
     }
 
     fn vid_dir_path(&self) -> PathBuf {
-        self.0.join("vid")
+        self.path.join("vid")
     }
 
     fn da_dir_path(&self) -> PathBuf {
-        self.0.join("da")
+        self.path.join("da")
+    }
+
+    fn chain_config_history_path(&self) -> PathBuf {
+        self.path.join("chain_config_history")
+    }
+
+    /// Read the config, along with whether it should be saved again to be stored under the current
+    /// encryption key.
+    fn read_config(&self) -> anyhow::Result<Option<(NetworkConfig, bool)>> {
+        let path = self.config_path();
+        if !path.is_file() {
+            tracing::info!("config not found at {}", path.display());
+            return Ok(None);
+        }
+        tracing::info!("loading config from {}", path.display());
+        let bytes = fs::read(&path).context("read")?;
+        if !encryption::is_encrypted(&bytes) {
+            let config = NetworkConfig::from_file(path.display().to_string())?;
+            return Ok(Some((config, self.encryption.is_some())));
+        }
+        let (bytes, reseal) = encryption::open(self.encryption.as_ref(), bytes)?;
+        Ok(Some((
+            serde_json::from_slice(&bytes).context("deserialize")?,
+            reseal,
+        )))
     }
 
     /// Overwrite a file if a condition is met.
@@ -118,19 +156,30 @@ impl Persistence {
 #[async_trait]
 impl SequencerPersistence for Persistence {
     async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
-        let path = self.config_path();
-        if !path.is_file() {
-            tracing::info!("config not found at {}", path.display());
-            return Ok(None);
-        }
-        tracing::info!("loading config from {}", path.display());
-        Ok(Some(NetworkConfig::from_file(path.display().to_string())?))
+        Ok(self.read_config()?.map(|(config, _)| config))
     }
 
     async fn save_config(&mut self, cfg: &NetworkConfig) -> anyhow::Result<()> {
         let path = self.config_path();
         tracing::info!("saving config to {}", path.display());
-        Ok(cfg.to_file(path.display().to_string())?)
+        let Some(encryption) = &self.encryption else {
+            return Ok(cfg.to_file(path.display().to_string())?);
+        };
+
+        // The config contains our private keys, so encrypt it if we can.
+        let bytes = encryption.encrypt(&serde_json::to_vec(cfg).context("serialize")?)?;
+        self.replace(
+            &path,
+            |_| Ok(true),
+            |mut file| {
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )
+    }
+
+    async fn config_needs_reseal(&self) -> anyhow::Result<bool> {
+        Ok(matches!(self.read_config()?, Some((_, true))))
     }
 
     async fn collect_garbage(&mut self, view: ViewNumber) -> anyhow::Result<()> {
@@ -353,6 +402,37 @@ impl SequencerPersistence for Persistence {
     async fn load_validated_state(&self, _header: &Header) -> anyhow::Result<ValidatedState> {
         bail!("state persistence not implemented");
     }
+
+    async fn load_chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>> {
+        let path = self.chain_config_history_path();
+        if !path.is_file() {
+            return Ok(vec![]);
+        }
+        let bytes = fs::read(path).context("read")?;
+        bincode::deserialize(&bytes).context("deserialize")
+    }
+
+    async fn load_latest_chain_config(&self) -> anyhow::Result<Option<ChainConfigActivation>> {
+        Ok(self.load_chain_config_history().await?.pop())
+    }
+
+    async fn append_chain_config(
+        &mut self,
+        activation: &ChainConfigActivation,
+    ) -> anyhow::Result<()> {
+        // The history is small, so we just rewrite the whole file on each (rare) update.
+        let mut history = self.load_chain_config_history().await?;
+        history.push(*activation);
+        self.replace(
+            &self.chain_config_history_path(),
+            |_| Ok(true),
+            |mut file| {
+                let bytes = bincode::serialize(&history).context("serialize")?;
+                file.write_all(&bytes)?;
+                Ok(())
+            },
+        )
+    }
 }
 
 #[cfg(test)]
@@ -369,8 +449,14 @@ mod testing {
             TempDir::new().unwrap()
         }
 
-        async fn connect(storage: &Self::Storage) -> Self {
-            Persistence(storage.path().into())
+        async fn connect_encrypted(
+            storage: &Self::Storage,
+            encryption: Option<Encryption>,
+        ) -> Self {
+            Persistence {
+                path: storage.path().into(),
+                encryption,
+            }
         }
     }
 }
@@ -2,7 +2,7 @@
 #![cfg(any(test, feature = "testing"))]
 
 use super::{NetworkConfig, PersistenceOptions, SequencerPersistence};
-use crate::{Header, Leaf, SeqTypes, ValidatedState, ViewNumber};
+use crate::{ChainConfigActivation, Header, Leaf, SeqTypes, ValidatedState, ViewNumber};
 use anyhow::bail;
 use async_trait::async_trait;
 use hotshot_types::{
@@ -41,6 +41,10 @@ impl SequencerPersistence for NoStorage {
         Ok(())
     }
 
+    async fn config_needs_reseal(&self) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
     async fn collect_garbage(&mut self, _view: ViewNumber) -> anyhow::Result<()> {
         Ok(())
     }
@@ -100,4 +104,16 @@ impl SequencerPersistence for NoStorage {
     async fn load_validated_state(&self, _header: &Header) -> anyhow::Result<ValidatedState> {
         bail!("state persistence not implemented");
     }
+
+    async fn load_chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>> {
+        Ok(vec![])
+    }
+
+    async fn load_latest_chain_config(&self) -> anyhow::Result<Option<ChainConfigActivation>> {
+        Ok(None)
+    }
+
+    async fn append_chain_config(&mut self, _: &ChainConfigActivation) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
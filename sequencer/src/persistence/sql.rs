@@ -1,20 +1,29 @@
-use super::{NetworkConfig, PersistenceOptions, SequencerPersistence};
+use super::{
+    encryption::{self, Encryption},
+    NetworkConfig, PersistenceOptions, SequencerPersistence,
+};
 use crate::{
     options::parse_duration,
     state::{BlockMerkleTree, FeeMerkleTree},
-    Header, Leaf, SeqTypes, ValidatedState, ViewNumber,
+    ChainConfigActivation, Header, Leaf, SeqTypes, ValidatedState, ViewNumber,
 };
 use anyhow::{bail, Context};
 use async_trait::async_trait;
 use clap::Parser;
 use derivative::Derivative;
-use futures::future::{BoxFuture, FutureExt};
+use ethers::utils::hex;
+use futures::{
+    future::{BoxFuture, FutureExt},
+    stream::TryStreamExt,
+};
 use hotshot_query_service::{
     data_source::{
         storage::{
             pruning::PrunerCfg,
             sql::{
-                include_migrations, postgres::types::ToSql, Config, Query, SqlStorage, Transaction,
+                include_migrations,
+                postgres::{types::ToSql, Row},
+                Config, Query, SqlStorage, Transaction,
             },
         },
         VersionedDataSource,
@@ -90,6 +99,10 @@ pub struct Options {
     /// Pruning parameters.
     #[clap(flatten)]
     pub pruning: PruningOptions,
+
+    /// Encryption of private data.
+    #[clap(flatten)]
+    pub encryption: encryption::Options,
 }
 
 impl TryFrom<Options> for Config {
@@ -208,7 +221,9 @@ impl PersistenceOptions for Options {
     type Persistence = Persistence;
 
     async fn create(self) -> anyhow::Result<Persistence> {
-        SqlStorage::connect(self.try_into()?).await
+        let encryption = self.encryption.load()?;
+        let db = SqlStorage::connect(self.try_into()?).await?;
+        Ok(Persistence { db, encryption })
     }
 
     async fn reset(self) -> anyhow::Result<()> {
@@ -217,11 +232,18 @@ impl PersistenceOptions for Options {
     }
 }
 
+/// Key of the JSON envelope holding an encrypted network config.
+const ENCRYPTED_CONFIG_KEY: &str = "encrypted";
+
 /// Postgres-backed persistence.
-pub type Persistence = SqlStorage;
+#[derive(Debug)]
+pub struct Persistence {
+    db: SqlStorage,
+    encryption: Option<Encryption>,
+}
 
 async fn transaction(
-    db: &mut Persistence,
+    db: &mut SqlStorage,
     f: impl FnOnce(Transaction) -> BoxFuture<anyhow::Result<()>>,
 ) -> anyhow::Result<()> {
     let tx = db.transaction().await?;
@@ -238,29 +260,61 @@ async fn transaction(
     }
 }
 
-#[async_trait]
-impl SequencerPersistence for Persistence {
-    async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
+impl Persistence {
+    /// Read the config, along with whether it should be saved again to be stored under the current
+    /// encryption key.
+    async fn read_config(&self) -> anyhow::Result<Option<(NetworkConfig, bool)>> {
         tracing::info!("loading config from Postgres");
 
         // Select the most recent config (although there should only be one).
         let Some(row) = self
+            .db
             .query_opt_static("SELECT config FROM network_config ORDER BY id DESC LIMIT 1")
             .await?
         else {
             tracing::info!("config not found");
             return Ok(None);
         };
-        let config = row.try_get("config")?;
-        Ok(serde_json::from_value(config)?)
+        let config: serde_json::Value = row.try_get("config")?;
+
+        // The config may be wrapped in an encrypted envelope; see `save_config`.
+        let config = match config.get(ENCRYPTED_CONFIG_KEY).and_then(|v| v.as_str()) {
+            Some(encrypted) => {
+                let bytes = hex::decode(encrypted).context("malformed encrypted config")?;
+                let (bytes, reseal) = encryption::open(self.encryption.as_ref(), bytes)?;
+                (serde_json::from_slice(&bytes)?, reseal)
+            }
+            // Plaintext configs must be encrypted if encryption has since been enabled.
+            None => (serde_json::from_value(config)?, self.encryption.is_some()),
+        };
+        Ok(Some(config))
+    }
+}
+
+#[async_trait]
+impl SequencerPersistence for Persistence {
+    async fn load_config(&self) -> anyhow::Result<Option<NetworkConfig>> {
+        Ok(self.read_config().await?.map(|(config, _)| config))
     }
 
     async fn save_config(&mut self, cfg: &NetworkConfig) -> anyhow::Result<()> {
         tracing::info!("saving config to Postgres");
-        let json = serde_json::to_value(cfg)?;
+        // The config contains our private keys, so if encryption is enabled we store it as an
+        // opaque blob inside a JSON envelope.
+        let json = match &self.encryption {
+            Some(encryption) => {
+                let bytes = encryption.encrypt(&serde_json::to_vec(cfg)?)?;
+                serde_json::json!({ ENCRYPTED_CONFIG_KEY: hex::encode(bytes) })
+            }
+            None => serde_json::to_value(cfg)?,
+        };
 
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
+                // Replace any existing config, so that no plaintext or stale copy of our keys is
+                // left behind.
+                tx.execute("DELETE FROM network_config", [] as [i64; 0])
+                    .await?;
                 tx.execute_one_with_retries(
                     "INSERT INTO network_config (config) VALUES ($1)",
                     [&json],
@@ -273,8 +327,12 @@ impl SequencerPersistence for Persistence {
         .await
     }
 
+    async fn config_needs_reseal(&self) -> anyhow::Result<bool> {
+        Ok(matches!(self.read_config().await?, Some((_, true))))
+    }
+
     async fn collect_garbage(&mut self, view: ViewNumber) -> anyhow::Result<()> {
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
                 let stmt1 = "DELETE FROM vid_share where view <= $1";
                 tx.execute(stmt1, [&(view.get_u64() as i64)]).await?;
@@ -317,7 +375,7 @@ impl SequencerPersistence for Persistence {
         let leaf_bytes = bincode::serialize(leaf)?;
         let qc_bytes = bincode::serialize(qc)?;
 
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
                 tx.execute_one_with_retries(
                     stmt,
@@ -338,6 +396,7 @@ impl SequencerPersistence for Persistence {
 
     async fn load_latest_acted_view(&self) -> anyhow::Result<Option<ViewNumber>> {
         Ok(self
+            .db
             .query_opt_static("SELECT view FROM highest_voted_view WHERE id = 0")
             .await?
             .map(|row| {
@@ -350,6 +409,7 @@ impl SequencerPersistence for Persistence {
         &self,
     ) -> anyhow::Result<Option<(Leaf, QuorumCertificate<SeqTypes>)>> {
         let Some(row) = self
+            .db
             .query_opt_static("SELECT leaf, qc FROM anchor_leaf WHERE id = 0")
             .await?
         else {
@@ -370,6 +430,7 @@ impl SequencerPersistence for Persistence {
         view: ViewNumber,
     ) -> anyhow::Result<Option<Proposal<SeqTypes, DAProposal<SeqTypes>>>> {
         let result = self
+            .db
             .query_opt(
                 "SELECT data FROM da_proposal where view = $1",
                 [&(view.get_u64() as i64)],
@@ -389,6 +450,7 @@ impl SequencerPersistence for Persistence {
         view: ViewNumber,
     ) -> anyhow::Result<Option<Proposal<SeqTypes, VidDisperseShare<SeqTypes>>>> {
         let result = self
+            .db
             .query_opt(
                 "SELECT data FROM vid_share where view = $1",
                 [&(view.get_u64() as i64)],
@@ -411,7 +473,7 @@ impl SequencerPersistence for Persistence {
         let view = data.get_view_number().get_u64();
         let data_bytes = bincode::serialize(proposal).unwrap();
 
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
                 tx.upsert(
                     "vid_share",
@@ -434,7 +496,7 @@ impl SequencerPersistence for Persistence {
         let view = data.get_view_number().get_u64();
         let data_bytes = bincode::serialize(proposal).unwrap();
 
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
                 tx.upsert(
                     "da_proposal",
@@ -458,7 +520,7 @@ impl SequencerPersistence for Persistence {
         INSERT INTO highest_voted_view (id, view) VALUES (0, $1)
         ON CONFLICT (id) DO UPDATE SET view = GREATEST(highest_voted_view.view, excluded.view)";
 
-        transaction(self, |mut tx| {
+        transaction(&mut self.db, |mut tx| {
             async move {
                 tx.execute_one_with_retries(stmt, [view.get_u64() as i64])
                     .await?;
@@ -480,6 +542,7 @@ impl SequencerPersistence for Persistence {
             let snapshot =
                 Snapshot::<_, BlockMerkleTree, { BlockMerkleTree::ARITY }>::Index(height);
             let frontier = self
+                .db
                 .get_path(snapshot, height - 1)
                 .await
                 .context("fetching frontier")?;
@@ -495,6 +558,7 @@ impl SequencerPersistence for Persistence {
         // may need to access.
         let snapshot = Snapshot::<_, FeeMerkleTree, { FeeMerkleTree::ARITY }>::Index(height);
         let fee_merkle_tree = self
+            .db
             .get_snapshot(snapshot)
             .await
             .context("loading fee merkle tree")?;
@@ -504,6 +568,62 @@ impl SequencerPersistence for Persistence {
             fee_merkle_tree,
         })
     }
+
+    async fn load_chain_config_history(&self) -> anyhow::Result<Vec<ChainConfigActivation>> {
+        self.db
+            .query_static("SELECT height, view, config FROM chain_config_history ORDER BY height")
+            .await?
+            .map_err(anyhow::Error::from)
+            .and_then(|row| async move { chain_config_activation(&row) })
+            .try_collect()
+            .await
+    }
+
+    async fn load_latest_chain_config(&self) -> anyhow::Result<Option<ChainConfigActivation>> {
+        self.db
+            .query_opt_static(
+                "SELECT height, view, config FROM chain_config_history
+                  ORDER BY height DESC LIMIT 1",
+            )
+            .await?
+            .map(|row| chain_config_activation(&row))
+            .transpose()
+    }
+
+    async fn append_chain_config(
+        &mut self,
+        activation: &ChainConfigActivation,
+    ) -> anyhow::Result<()> {
+        let height = activation.height as i64;
+        let view = activation.view as i64;
+        let config = serde_json::to_value(activation.config)?;
+
+        transaction(&mut self.db, |mut tx| {
+            async move {
+                tx.upsert(
+                    "chain_config_history",
+                    ["height", "view", "config"],
+                    ["height"],
+                    [[sql_param(&height), sql_param(&view), sql_param(&config)]],
+                )
+                .await?;
+                Ok(())
+            }
+            .boxed()
+        })
+        .await
+    }
+}
+
+fn chain_config_activation(row: &Row) -> anyhow::Result<ChainConfigActivation> {
+    let height: i64 = row.try_get("height")?;
+    let view: i64 = row.try_get("view")?;
+    let config = row.try_get("config")?;
+    Ok(ChainConfigActivation {
+        height: height as u64,
+        view: view as u64,
+        config: serde_json::from_value(config)?,
+    })
 }
 
 fn sql_param<T: ToSql + Sync>(param: &T) -> &(dyn ToSql + Sync) {
@@ -524,8 +644,8 @@ mod testing {
             TmpDb::init().await
         }
 
-        async fn connect(db: &Self::Storage) -> Self {
-            Options {
+        async fn connect_encrypted(db: &Self::Storage, encryption: Option<Encryption>) -> Self {
+            let mut persistence = Options {
                 port: Some(db.port()),
                 host: Some(db.host()),
                 user: Some("postgres".into()),
@@ -534,7 +654,9 @@ mod testing {
             }
             .create()
             .await
-            .unwrap()
+            .unwrap();
+            persistence.encryption = encryption;
+            persistence
         }
     }
 }
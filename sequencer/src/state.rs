@@ -215,14 +215,9 @@ pub fn validate_proposal(
         )
     );
 
-    anyhow::ensure!(
-        get_proposed_payload_size() < expected_chain_config.max_block_size(),
-        anyhow::anyhow!(
-            "Invalid Payload Size: local={:?}, proposal={:?}",
-            expected_chain_config,
-            proposal.chain_config
-        )
-    );
+    expected_chain_config
+        .check_block_size(get_proposed_payload_size())
+        .context("Invalid Payload Size")?;
 
     // validate height
     anyhow::ensure!(
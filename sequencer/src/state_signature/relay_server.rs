@@ -10,7 +10,7 @@ use hotshot_types::light_client::{
 };
 use jf_primitives::signatures::SignatureScheme;
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap},
     path::PathBuf,
 };
 use tide_disco::{
@@ -22,6 +22,10 @@ use tide_disco::{
 use url::Url;
 use vbs::version::StaticVersionType;
 
+/// Number of recent available signature bundles to keep, so that the prover can still fetch the
+/// state at the end of an epoch after newer states have become available.
+const AVAILABLE_BUNDLE_CAPACITY: usize = 10_000;
+
 /// State that checks the light client state update and the signature collection
 #[derive(Default)]
 struct StateRelayServerState {
@@ -36,6 +40,8 @@ struct StateRelayServerState {
     latest_available_bundle: Option<StateSignaturesBundle>,
     /// The block height of the latest available state signature bundle
     latest_block_height: Option<u64>,
+    /// Recent available state signature bundles, by block height
+    available_bundles: BTreeMap<u64, StateSignaturesBundle>,
 
     /// A ordered queue of block heights, used for garbage collection.
     queue: BTreeSet<u64>,
@@ -72,6 +78,12 @@ pub trait StateRelayServerDataSource {
     /// Errors if there's no available signatures bundle.
     fn get_latest_signature_bundle(&self) -> Result<StateSignaturesBundle, Error>;
 
+    /// Get the available signatures bundle for the given block height.
+    /// # Errors
+    /// Errors if there's no available signatures bundle at this height, either because it is not
+    /// ready yet or because it is too old.
+    fn get_signature_bundle(&self, height: u64) -> Result<StateSignaturesBundle, Error>;
+
     /// Post a signature to the relay server
     /// # Errors
     /// Errors if the signature is invalid, already posted, or no longer needed.
@@ -94,6 +106,18 @@ impl StateRelayServerDataSource for StateRelayServerState {
         }
     }
 
+    fn get_signature_bundle(&self, height: u64) -> Result<StateSignaturesBundle, Error> {
+        match self.available_bundles.get(&height) {
+            Some(bundle) => Ok(bundle.clone()),
+            None => Err(tide_disco::error::ServerError::catch_all(
+                StatusCode::NotFound,
+                format!(
+                    "The light client state signatures at block height {height} are not available."
+                ),
+            )),
+        }
+    }
+
     fn post_signature(
         &mut self,
         key: StateVerKey,
@@ -158,6 +182,10 @@ impl StateRelayServerDataSource for StateRelayServerState {
             );
             self.latest_block_height = Some(block_height);
             self.latest_available_bundle = Some(bundle.clone());
+            self.available_bundles.insert(block_height, bundle.clone());
+            while self.available_bundles.len() > AVAILABLE_BUNDLE_CAPACITY {
+                self.available_bundles.pop_first();
+            }
             while let Some(height) = self.queue.pop_first() {
                 self.bundles.remove(&height);
                 if height == block_height {
@@ -205,6 +233,15 @@ where
     api.get("getlateststate", |_req, state| {
         async move { state.get_latest_signature_bundle() }.boxed()
     })?
+    .get("getstate", |req, state| {
+        async move {
+            let height = req
+                .integer_param("height")
+                .map_err(Error::from_request_error)?;
+            state.get_signature_bundle(height)
+        }
+        .boxed()
+    })?
     .post("poststatesignature", |req, state| {
         async move {
             let StateSignatureRequestBody {
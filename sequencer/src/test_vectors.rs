@@ -0,0 +1,267 @@
+//! Language-independent test vectors for the sequencer's Merkle trees.
+//!
+//! Ports of the sequencer state to other languages (e.g. Solidity or Go code verifying fee or
+//! block Merkle proofs) can check that they hash and serialize exactly like this crate by
+//! reproducing the roots and verifying the proofs in these vectors. The vectors are generated
+//! deterministically, and can be written out as JSON with the `gen-test-vectors` binary. The output
+//! of that binary with default options is published as `data/test_vectors.json`, and a test checks
+//! that this crate still reproduces it exactly.
+//!
+//! All byte strings are the canonical serializations used for hashing:
+//! * fee tree keys are 20-byte account addresses and values are 32-byte little-endian balances;
+//! * block tree leaves are 32-byte header commitments;
+//! * roots are the 32-byte digests of the tree root.
+
+use crate::{
+    state::{
+        BlockMerkleCommitment, BlockMerkleTree, FeeAccount, FeeAccountProof, FeeAmount,
+        FeeMerkleCommitment, FeeMerkleTree,
+    },
+    Header, ValidatedState,
+};
+use anyhow::{ensure, Context};
+use ark_serialize::CanonicalSerialize;
+use committable::{Commitment, RawCommitmentBuilder};
+use ethers::types::{Address, Bytes, U256};
+use jf_primitives::merkle_tree::{
+    prelude::SHA3MerkleTree, AppendableMerkleTreeScheme, LookupResult, MerkleCommitment,
+    MerkleTreeScheme,
+};
+use serde::{Deserialize, Serialize};
+
+type BlockMerkleProof = <BlockMerkleTree as MerkleTreeScheme>::MembershipProof;
+
+/// Number of leaves in each tree of the published test vectors.
+pub const DEFAULT_NUM_ENTRIES: u64 = 16;
+
+/// Test vectors for all of the sequencer's Merkle trees.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TestVectors {
+    pub fee_merkle_tree: FeeTreeVectors,
+    pub block_merkle_tree: BlockTreeVectors,
+}
+
+/// Test vectors for the fee Merkle tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeeTreeVectors {
+    pub height: usize,
+    pub arity: usize,
+    pub entries: Vec<FeeTreeEntry>,
+    /// Digest of the root of the tree containing `entries`.
+    pub root: Bytes,
+    /// The root as it appears in the `fee_merkle_tree_root` field of a header.
+    pub commitment: FeeMerkleCommitment,
+    /// Membership proofs for each entry, followed by non-membership proofs.
+    pub proofs: Vec<FeeAccountProof>,
+}
+
+/// A leaf of the fee Merkle tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FeeTreeEntry {
+    pub account: Address,
+    pub balance: U256,
+    /// Serialization of `account` as a leaf key.
+    pub key: Bytes,
+    /// Serialization of `balance` as a leaf value.
+    pub value: Bytes,
+}
+
+/// Test vectors for the block Merkle tree.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockTreeVectors {
+    pub height: usize,
+    pub arity: usize,
+    /// Serialized header commitments appended to the tree, in order.
+    pub entries: Vec<Bytes>,
+    /// Digest of the root of the tree containing `entries`.
+    pub root: Bytes,
+    /// The root as it appears in the `block_merkle_tree_root` field of a header.
+    pub commitment: BlockMerkleCommitment,
+    /// Membership proofs for each entry, by position.
+    pub proofs: Vec<BlockMerkleProof>,
+}
+
+impl TestVectors {
+    /// Deterministically generate test vectors with `num_entries` leaves in each tree.
+    pub fn generate(num_entries: u64) -> anyhow::Result<Self> {
+        // Start from empty trees with the same parameters as the real state.
+        let ValidatedState {
+            mut fee_merkle_tree,
+            block_merkle_tree,
+        } = ValidatedState::default();
+
+        let mut entries = vec![];
+        for i in 0..num_entries {
+            let account = account(i);
+            let balance = U256::from(1_000_000_000u64) * (i + 1);
+            fee_merkle_tree.update(FeeAccount::from(account), FeeAmount::from(balance))?;
+            entries.push(FeeTreeEntry {
+                account,
+                balance,
+                key: canonical_bytes(&FeeAccount::from(account))?,
+                value: canonical_bytes(&FeeAmount::from(balance))?,
+            });
+        }
+        // Prove every account which is present, and a few which are not.
+        let proofs = (0..num_entries + 2)
+            .map(|i| {
+                Ok(FeeAccountProof::prove(&fee_merkle_tree, account(i))
+                    .context("fee tree is missing data")?
+                    .0)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let fee_merkle_tree = FeeTreeVectors {
+            height: fee_merkle_tree.height(),
+            arity: FeeMerkleTree::ARITY,
+            entries,
+            root: canonical_bytes(&fee_merkle_tree.commitment().digest())?,
+            commitment: fee_merkle_tree.commitment(),
+            proofs,
+        };
+
+        // The block tree in the state only remembers its frontier, so build a full tree with the same
+        // height and hash function in order to prove all entries.
+        let comms = (0..num_entries).map(header_commitment).collect::<Vec<_>>();
+        let block_merkle_tree =
+            SHA3MerkleTree::from_elems(Some(block_merkle_tree.height()), &comms)?;
+        let entries = comms
+            .iter()
+            .map(canonical_bytes)
+            .collect::<anyhow::Result<_>>()?;
+        let proofs = (0..num_entries)
+            .map(|i| match block_merkle_tree.lookup(i) {
+                LookupResult::Ok(_, proof) => Ok(proof),
+                _ => anyhow::bail!("block tree is missing entry {i}"),
+            })
+            .collect::<anyhow::Result<_>>()?;
+        let block_merkle_tree = BlockTreeVectors {
+            height: block_merkle_tree.height(),
+            arity: BlockMerkleTree::ARITY,
+            entries,
+            root: canonical_bytes(&block_merkle_tree.commitment().digest())?,
+            commitment: block_merkle_tree.commitment(),
+            proofs,
+        };
+
+        Ok(Self {
+            fee_merkle_tree,
+            block_merkle_tree,
+        })
+    }
+
+    /// Check that these vectors are consistent with this implementation.
+    ///
+    /// The trees are rebuilt from the entries and their roots compared with the expected roots,
+    /// and all proofs are verified against those roots.
+    pub fn verify(&self) -> anyhow::Result<()> {
+        let ValidatedState {
+            mut fee_merkle_tree,
+            mut block_merkle_tree,
+        } = ValidatedState::default();
+
+        let fee = &self.fee_merkle_tree;
+        for entry in &fee.entries {
+            ensure!(
+                entry.key == canonical_bytes(&FeeAccount::from(entry.account))?,
+                "wrong key encoding for {:#x}",
+                entry.account
+            );
+            ensure!(
+                entry.value == canonical_bytes(&FeeAmount::from(entry.balance))?,
+                "wrong value encoding for {:#x}",
+                entry.account
+            );
+            fee_merkle_tree.update(
+                FeeAccount::from(entry.account),
+                FeeAmount::from(entry.balance),
+            )?;
+        }
+        ensure!(
+            fee_merkle_tree.commitment() == fee.commitment
+                && canonical_bytes(&fee.commitment.digest())? == fee.root,
+            "fee tree root mismatch"
+        );
+        for proof in &fee.proofs {
+            proof.verify(&fee.commitment)?;
+        }
+
+        let block = &self.block_merkle_tree;
+        ensure!(
+            block.entries.len() == block.proofs.len(),
+            "expected one block tree proof per entry"
+        );
+        for (i, entry) in block.entries.iter().enumerate() {
+            let comm = header_commitment(i as u64);
+            ensure!(
+                *entry == canonical_bytes(&comm)?,
+                "block tree entry {i} was not generated by this implementation"
+            );
+            block_merkle_tree.push(comm)?;
+        }
+        ensure!(
+            block_merkle_tree.commitment() == block.commitment
+                && canonical_bytes(&block.commitment.digest())? == block.root,
+            "block tree root mismatch"
+        );
+        for (i, proof) in block.proofs.iter().enumerate() {
+            ensure!(
+                BlockMerkleTree::verify(block.commitment.digest(), i as u64, proof)?.is_ok(),
+                "invalid block tree proof for entry {i}"
+            );
+        }
+        Ok(())
+    }
+}
+
+fn account(i: u64) -> Address {
+    Address::from_low_u64_be(i + 1)
+}
+
+fn header_commitment(i: u64) -> Commitment<Header> {
+    RawCommitmentBuilder::new("TEST_VECTOR")
+        .u64_field("index", i)
+        .finalize()
+}
+
+fn canonical_bytes(x: &impl CanonicalSerialize) -> anyhow::Result<Bytes> {
+    let mut bytes = vec![];
+    x.serialize_uncompressed(&mut bytes)?;
+    Ok(bytes.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The published test vectors, as written by `gen-test-vectors` with default options.
+    const GOLDEN: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../data/test_vectors.json");
+
+    #[test]
+    fn test_vectors_golden() {
+        let expected = std::fs::read_to_string(GOLDEN).unwrap_or_else(|err| {
+            panic!(
+                "cannot read {GOLDEN}: {err}; generate it with \
+                 `cargo run --bin gen-test-vectors -- --out data/test_vectors.json`"
+            )
+        });
+        let actual =
+            serde_json::to_string_pretty(&TestVectors::generate(DEFAULT_NUM_ENTRIES).unwrap())
+                .unwrap();
+        // Ports of the sequencer test against the published file, so any difference is a breaking
+        // change. If it is intended, regenerate the file with `gen-test-vectors`.
+        assert!(
+            actual == expected,
+            "test vectors differ from {GOLDEN}; actual vectors:\n{actual}"
+        );
+
+        let vectors: TestVectors = serde_json::from_str(&expected).unwrap();
+        vectors.verify().unwrap();
+    }
+
+    #[test]
+    fn test_vectors_detect_tampering() {
+        let mut vectors = TestVectors::generate(3).unwrap();
+        vectors.fee_merkle_tree.entries[0].balance += 1.into();
+        vectors.verify().unwrap_err();
+    }
+}
@@ -0,0 +1,321 @@
+//! Tracing of recently submitted transactions through the sequencing pipeline.
+//!
+//! When a user reports that a transaction disappeared, the node it was submitted to is usually the
+//! only one that knows the transaction ever existed. [`TxTracer`] records each stage a transaction
+//! submitted through this node passes (submission, inclusion in a DA proposal, decision, and
+//! indexing in the query service) so that operators can tell where it got stuck. To bound memory
+//! usage, only the most recently submitted transactions are tracked.
+
+use crate::{block::entry::TxTableEntryWord, Payload, SeqTypes, Transaction};
+use async_std::sync::RwLock;
+use committable::Commitment;
+use hotshot::types::{Event, EventType};
+use hotshot_types::{
+    traits::{block_contents::BlockHeader, node_implementation::ConsensusTime, BlockPayload},
+    vote::HasViewNumber,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use time::OffsetDateTime;
+
+/// Default number of transactions whose traces are kept in memory.
+pub const DEFAULT_TX_TRACE_CAPACITY: usize = 10_000;
+
+/// A stage in the life of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum TxStage {
+    /// The transaction was accepted by this node and broadcast to builders.
+    Submitted,
+    /// The transaction was rejected by this node.
+    Rejected { reason: String },
+    /// The transaction was included in a DA proposal.
+    Proposed { view: u64 },
+    /// A block containing the transaction was decided.
+    Decided { height: u64, view: u64 },
+    /// The block containing the transaction is available from this node's query service.
+    Indexed { height: u64 },
+}
+
+/// A stage reached by a transaction, and when this node observed it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxStageEvent {
+    #[serde(flatten)]
+    pub stage: TxStage,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// The stages a transaction has passed through so far, in the order they were observed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxTrace {
+    pub hash: Commitment<Transaction>,
+    pub events: Vec<TxStageEvent>,
+}
+
+/// Bounded buffer of traces for transactions submitted through this node.
+#[derive(Debug)]
+pub struct TxTracer {
+    inner: RwLock<TraceBuffer>,
+}
+
+#[derive(Debug)]
+struct TraceBuffer {
+    capacity: usize,
+    traces: HashMap<Commitment<Transaction>, Vec<TxStageEvent>>,
+    // Transactions in the order they were submitted, so the oldest can be evicted when full.
+    order: VecDeque<Commitment<Transaction>>,
+}
+
+impl Default for TxTracer {
+    fn default() -> Self {
+        Self::new(DEFAULT_TX_TRACE_CAPACITY)
+    }
+}
+
+impl TxTracer {
+    /// Create a tracer which keeps traces for up to `capacity` transactions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: RwLock::new(TraceBuffer {
+                capacity,
+                traces: Default::default(),
+                order: Default::default(),
+            }),
+        }
+    }
+
+    /// Start tracing a transaction which was accepted by this node.
+    pub async fn submitted(&self, hash: Commitment<Transaction>) {
+        self.inner.write().await.start(hash, TxStage::Submitted);
+    }
+
+    /// Record that this node rejected a transaction.
+    pub async fn rejected(&self, hash: Commitment<Transaction>, reason: impl ToString) {
+        self.inner.write().await.start(
+            hash,
+            TxStage::Rejected {
+                reason: reason.to_string(),
+            },
+        );
+    }
+
+    /// Keep traces for up to `capacity` transactions, evicting the oldest ones if necessary.
+    pub async fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.write().await;
+        inner.capacity = capacity;
+        inner.evict_to(capacity);
+    }
+
+    /// Update traces with the progress reported by a consensus event.
+    pub async fn handle_event(&self, event: &Event<SeqTypes>) {
+        // Decoding every block is expensive, so don't bother unless there is something to trace.
+        if self.inner.read().await.traces.is_empty() {
+            return;
+        }
+        match &event.event {
+            EventType::DAProposal { proposal, .. } => {
+                let view = proposal.data.get_view_number().get_u64();
+                let payload: Payload<TxTableEntryWord> = BlockPayload::from_bytes(
+                    &proposal.data.encoded_transactions,
+                    &proposal.data.metadata,
+                );
+                let txs = payload.transaction_commitments(&proposal.data.metadata);
+                self.inner
+                    .write()
+                    .await
+                    .record(txs, || TxStage::Proposed { view });
+            }
+            EventType::Decide { leaf_chain, .. } => {
+                let mut inner = self.inner.write().await;
+                for info in leaf_chain.iter().rev() {
+                    let leaf = &info.leaf;
+                    let Some(payload) = leaf.get_block_payload() else {
+                        continue;
+                    };
+                    let header = leaf.get_block_header();
+                    let height = header.height;
+                    let view = leaf.get_view_number().get_u64();
+                    let txs = payload.transaction_commitments(header.metadata());
+                    inner.record(txs, || TxStage::Decided { height, view });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Record that the blocks up to and including `height` are available from the query service.
+    ///
+    /// This only needs the block height, since the transactions in each block were already found
+    /// when the block was decided.
+    pub async fn indexed(&self, height: u64) {
+        let mut inner = self.inner.write().await;
+        for events in inner.traces.values_mut() {
+            let Some(TxStageEvent {
+                stage: TxStage::Decided {
+                    height: decided, ..
+                },
+                ..
+            }) = events.last()
+            else {
+                continue;
+            };
+            if *decided <= height {
+                let height = *decided;
+                events.push(TxStageEvent {
+                    stage: TxStage::Indexed { height },
+                    timestamp: now(),
+                });
+            }
+        }
+    }
+
+    /// Get the trace of a transaction, if it was submitted through this node recently.
+    pub async fn get(&self, hash: Commitment<Transaction>) -> Option<TxTrace> {
+        let inner = self.inner.read().await;
+        let events = inner.traces.get(&hash)?.clone();
+        Some(TxTrace { hash, events })
+    }
+}
+
+impl TraceBuffer {
+    fn start(&mut self, hash: Commitment<Transaction>, stage: TxStage) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.traces.contains_key(&hash) {
+            self.evict_to(self.capacity - 1);
+            self.order.push_back(hash);
+        }
+        self.traces.entry(hash).or_default().push(TxStageEvent {
+            stage,
+            timestamp: now(),
+        });
+    }
+
+    /// Drop the oldest traces until at most `len` remain.
+    fn evict_to(&mut self, len: usize) {
+        while self.order.len() > len {
+            if let Some(oldest) = self.order.pop_front() {
+                self.traces.remove(&oldest);
+            }
+        }
+    }
+
+    /// Record `stage` for each transaction in `txs` which is being traced.
+    fn record(
+        &mut self,
+        txs: impl IntoIterator<Item = Commitment<Transaction>>,
+        stage: impl Fn() -> TxStage,
+    ) {
+        for tx in txs {
+            if let Some(events) = self.traces.get_mut(&tx) {
+                events.push(TxStageEvent {
+                    stage: stage(),
+                    timestamp: now(),
+                });
+            }
+        }
+    }
+}
+
+fn now() -> u64 {
+    (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::NamespaceId;
+    use committable::Committable;
+
+    fn tx(i: u8) -> Commitment<Transaction> {
+        Transaction::new(NamespaceId::from(1), vec![i]).commit()
+    }
+
+    #[async_std::test]
+    async fn test_tx_tracer_eviction() {
+        let tracer = TxTracer::new(2);
+        tracer.submitted(tx(0)).await;
+        tracer.rejected(tx(1), "too big").await;
+
+        let trace = tracer.get(tx(1)).await.unwrap();
+        assert_eq!(
+            trace.events[0].stage,
+            TxStage::Rejected {
+                reason: "too big".into()
+            }
+        );
+
+        // Stages are only recorded for transactions which are being traced.
+        tracer
+            .inner
+            .write()
+            .await
+            .record([tx(0), tx(2)], || TxStage::Proposed { view: 1 });
+        let stages = tracer
+            .get(tx(0))
+            .await
+            .unwrap()
+            .events
+            .into_iter()
+            .map(|event| event.stage)
+            .collect::<Vec<_>>();
+        assert_eq!(stages, [TxStage::Submitted, TxStage::Proposed { view: 1 }]);
+        assert_eq!(tracer.get(tx(2)).await, None);
+
+        // Once full, the oldest transaction is evicted.
+        tracer.submitted(tx(2)).await;
+        assert_eq!(tracer.get(tx(0)).await, None);
+        tracer.get(tx(1)).await.unwrap();
+        tracer.get(tx(2)).await.unwrap();
+
+        // Shrinking the buffer evicts the oldest transactions right away.
+        tracer.set_capacity(1).await;
+        assert_eq!(tracer.get(tx(1)).await, None);
+        tracer.get(tx(2)).await.unwrap();
+    }
+
+    #[async_std::test]
+    async fn test_tx_tracer_indexed() {
+        let tracer = TxTracer::new(3);
+        for i in 0..3 {
+            tracer.submitted(tx(i)).await;
+        }
+        {
+            let mut inner = tracer.inner.write().await;
+            inner.record([tx(0)], || TxStage::Decided { height: 1, view: 1 });
+            inner.record([tx(1)], || TxStage::Decided { height: 2, view: 2 });
+        }
+
+        // Only transactions decided at or below the indexed height are marked, and only once.
+        tracer.indexed(1).await;
+        tracer.indexed(1).await;
+        let stages = |i| {
+            let tracer = &tracer;
+            async move {
+                tracer
+                    .get(tx(i))
+                    .await
+                    .unwrap()
+                    .events
+                    .into_iter()
+                    .map(|event| event.stage)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            stages(0).await,
+            [
+                TxStage::Submitted,
+                TxStage::Decided { height: 1, view: 1 },
+                TxStage::Indexed { height: 1 }
+            ]
+        );
+        assert_eq!(
+            stages(1).await,
+            [TxStage::Submitted, TxStage::Decided { height: 2, view: 2 }]
+        );
+        assert_eq!(stages(2).await, [TxStage::Submitted]);
+    }
+}
@@ -1,20 +1,53 @@
-use anyhow::{ensure, Context};
-use async_std::sync::Arc;
+use anyhow::{bail, ensure, Context};
+use async_std::{sync::Arc, task::sleep};
 use clap::{builder::OsStr, Parser};
 use contract_bindings::{
-    light_client::LIGHTCLIENT_ABI, light_client_mock::LIGHTCLIENTMOCK_ABI,
-    light_client_state_update_vk::LightClientStateUpdateVK,
-    light_client_state_update_vk_mock::LightClientStateUpdateVKMock, plonk_verifier::PlonkVerifier,
+    erc1967_proxy::{
+        ERC1967Proxy, ERC1967PROXY_ABI, ERC1967PROXY_BYTECODE, ERC1967PROXY_DEPLOYED_BYTECODE,
+    },
+    fee_contract::{DepositFilter, FeeContract, FeeContractErrors},
+    hot_shot::{HotShot, HOTSHOT_BYTECODE, HOTSHOT_DEPLOYED_BYTECODE},
+    light_client::{
+        LightClient, LightClientErrors, LIGHTCLIENT_ABI, LIGHTCLIENT_BYTECODE,
+        LIGHTCLIENT_DEPLOYED_BYTECODE,
+    },
+    light_client_mock::{
+        LIGHTCLIENTMOCK_ABI, LIGHTCLIENTMOCK_BYTECODE, LIGHTCLIENTMOCK_DEPLOYED_BYTECODE,
+    },
+    light_client_state_update_vk::{
+        LightClientStateUpdateVK, LIGHTCLIENTSTATEUPDATEVK_BYTECODE,
+        LIGHTCLIENTSTATEUPDATEVK_DEPLOYED_BYTECODE,
+    },
+    light_client_state_update_vk_mock::{
+        LightClientStateUpdateVKMock, LIGHTCLIENTSTATEUPDATEVKMOCK_BYTECODE,
+        LIGHTCLIENTSTATEUPDATEVKMOCK_DEPLOYED_BYTECODE,
+    },
+    plonk_verifier::{PlonkVerifier, PLONKVERIFIER_BYTECODE, PLONKVERIFIER_DEPLOYED_BYTECODE},
     shared_types::LightClientState,
 };
 use derive_more::Display;
-use ethers::{prelude::*, solc::artifacts::BytecodeObject};
+use ethers::{
+    abi::{self, Abi, Detokenize, Param, Token},
+    prelude::*,
+    solc::artifacts::BytecodeObject,
+    types::transaction::eip2718::TypedTransaction,
+};
 use futures::future::{BoxFuture, FutureExt};
 use hotshot_contract_adapter::light_client::ParsedLightClientState;
-use std::{collections::HashMap, io::Write, ops::Deref};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{stderr, stdin, Write},
+    ops::Deref,
+    str::FromStr,
+    time::Duration,
+};
+use url::Url;
 
 /// Set of predeployed contracts.
-#[derive(Clone, Debug, Parser)]
+#[derive(Clone, Debug, Default, Parser, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct DeployedContracts {
     /// Use an already-deployed HotShot.sol instead of deploying a new one.
     #[clap(long, env = Contract::HotShot)]
@@ -37,8 +70,23 @@ pub struct DeployedContracts {
     light_client_proxy: Option<Address>,
 }
 
+impl DeployedContracts {
+    /// Use predeployed contracts from `other` for any contracts not already specified in `self`.
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            hotshot: self.hotshot.or(other.hotshot),
+            plonk_verifier: self.plonk_verifier.or(other.plonk_verifier),
+            light_client_state_update_vk: self
+                .light_client_state_update_vk
+                .or(other.light_client_state_update_vk),
+            light_client: self.light_client.or(other.light_client),
+            light_client_proxy: self.light_client_proxy.or(other.light_client_proxy),
+        }
+    }
+}
+
 /// An identifier for a particular contract.
-#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
 pub enum Contract {
     #[display(fmt = "ESPRESSO_SEQUENCER_HOTSHOT_ADDRESS")]
     HotShot,
@@ -58,9 +106,420 @@ impl From<Contract> for OsStr {
     }
 }
 
+/// Maximum size in bytes of deployed contract code, as defined in EIP-170.
+pub const MAX_RUNTIME_CODE_SIZE: usize = 24_576;
+
+/// Maximum size in bytes of contract init code, as defined in EIP-3860.
+pub const MAX_INIT_CODE_SIZE: usize = 2 * MAX_RUNTIME_CODE_SIZE;
+
+/// Contract size limits enforced by the target chain.
+///
+/// The default is the limits of Ethereum mainnet. Some L1s and L2s allow larger contracts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BytecodeLimits {
+    pub max_runtime_code_size: usize,
+    pub max_init_code_size: usize,
+}
+
+impl Default for BytecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_runtime_code_size: MAX_RUNTIME_CODE_SIZE,
+            max_init_code_size: MAX_INIT_CODE_SIZE,
+        }
+    }
+}
+
+impl BytecodeLimits {
+    /// Check that init code for `name` can be executed on the target chain.
+    pub fn check_init_code(&self, name: Contract, init_code: &[u8]) -> anyhow::Result<()> {
+        if init_code.len() > self.max_init_code_size {
+            bail!(
+                "init code of {name:?} is {} bytes, exceeding the limit of {} bytes by {} bytes",
+                init_code.len(),
+                self.max_init_code_size,
+                init_code.len() - self.max_init_code_size
+            );
+        }
+        Ok(())
+    }
+
+    /// Check that runtime code for `name` can be deployed on the target chain.
+    pub fn check_runtime_code(&self, name: Contract, runtime_code: &[u8]) -> anyhow::Result<()> {
+        if runtime_code.len() > self.max_runtime_code_size {
+            bail!(
+                "runtime code of {name:?} is {} bytes, exceeding the limit of {} bytes by {} bytes",
+                runtime_code.len(),
+                self.max_runtime_code_size,
+                runtime_code.len() - self.max_runtime_code_size
+            );
+        }
+        Ok(())
+    }
+
+    /// Check the size of every contract a deployment may need before sending any transactions.
+    ///
+    /// If `mock` is set, the mock light client contracts are checked instead of the real ones.
+    pub fn check_all(&self, mock: bool) -> anyhow::Result<()> {
+        let (light_client, vk) = if mock {
+            (
+                (
+                    &LIGHTCLIENTMOCK_BYTECODE,
+                    &LIGHTCLIENTMOCK_DEPLOYED_BYTECODE,
+                ),
+                (
+                    &LIGHTCLIENTSTATEUPDATEVKMOCK_BYTECODE,
+                    &LIGHTCLIENTSTATEUPDATEVKMOCK_DEPLOYED_BYTECODE,
+                ),
+            )
+        } else {
+            (
+                (&LIGHTCLIENT_BYTECODE, &LIGHTCLIENT_DEPLOYED_BYTECODE),
+                (
+                    &LIGHTCLIENTSTATEUPDATEVK_BYTECODE,
+                    &LIGHTCLIENTSTATEUPDATEVK_DEPLOYED_BYTECODE,
+                ),
+            )
+        };
+        let contracts = [
+            (
+                Contract::HotShot,
+                (&HOTSHOT_BYTECODE, &HOTSHOT_DEPLOYED_BYTECODE),
+            ),
+            (
+                Contract::PlonkVerifier,
+                (&PLONKVERIFIER_BYTECODE, &PLONKVERIFIER_DEPLOYED_BYTECODE),
+            ),
+            (Contract::StateUpdateVK, vk),
+            (Contract::LightClient, light_client),
+            (
+                Contract::LightClientProxy,
+                (&ERC1967PROXY_BYTECODE, &ERC1967PROXY_DEPLOYED_BYTECODE),
+            ),
+        ];
+        for (name, (init_code, runtime_code)) in contracts {
+            self.check_init_code(name, init_code)?;
+            self.check_runtime_code(name, runtime_code)?;
+        }
+        Ok(())
+    }
+}
+
+/// A state-changing transaction which is about to be sent, described for review by an operator.
+#[derive(Clone, Debug)]
+pub struct PlannedTx {
+    /// Name of the contract being deployed or called.
+    pub contract: String,
+    /// Address of the contract being called, or `None` for a deployment.
+    pub to: Option<Address>,
+    /// The function being called, or `constructor` for a deployment.
+    pub function: String,
+    /// Decoded arguments as `(name, value)` pairs.
+    pub args: Vec<(String, String)>,
+    /// Estimated gas, if the transaction could be simulated.
+    pub gas: Option<U256>,
+}
+
+impl PlannedTx {
+    /// Describe the deployment of `contract` by the deploy transaction `tx`.
+    pub async fn deployment<M: Middleware>(
+        contract: impl ToString,
+        abi: &Abi,
+        tx: &TypedTransaction,
+        l1: &M,
+    ) -> Self {
+        let args = match (&abi.constructor, tx.data()) {
+            (Some(constructor), Some(init_code)) => {
+                decode_constructor_args(&constructor.inputs, init_code)
+            }
+            _ => vec![],
+        };
+        Self {
+            contract: contract.to_string(),
+            to: None,
+            function: "constructor".into(),
+            args,
+            gas: l1.estimate_gas(tx, None).await.ok(),
+        }
+    }
+
+    /// Describe a call to a function of `contract`.
+    pub async fn call<M: Middleware, D: Detokenize>(
+        contract: impl ToString,
+        call: &ContractCall<M, D>,
+    ) -> Self {
+        let args = call
+            .tx
+            .data()
+            .and_then(|data| call.function.decode_input(data.get(4..)?).ok())
+            .map(|tokens| named_args(&call.function.inputs, tokens))
+            .unwrap_or_default();
+        Self {
+            contract: contract.to_string(),
+            to: call.tx.to_addr().copied(),
+            function: call.function.name.clone(),
+            args,
+            gas: call.estimate_gas().await.ok(),
+        }
+    }
+}
+
+impl fmt::Display for PlannedTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.to {
+            Some(to) => writeln!(f, "call {}.{} at {to:#x}", self.contract, self.function)?,
+            None => writeln!(f, "deploy {}", self.contract)?,
+        }
+        for (name, value) in &self.args {
+            writeln!(f, "  {name}: {value}")?;
+        }
+        match self.gas {
+            Some(gas) => write!(f, "  estimated gas: {gas}"),
+            None => write!(f, "  estimated gas: unknown (simulation failed)"),
+        }
+    }
+}
+
+/// Whether the operator is asked to approve each transaction before it is sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Confirmation {
+    /// Send transactions without asking.
+    #[default]
+    Auto,
+    /// Print each transaction and send it only if the operator confirms on stdin.
+    Interactive,
+}
+
+impl Confirmation {
+    /// Get approval to send `tx`.
+    ///
+    /// Fails if the operator declines, aborting the deployment before the transaction is sent.
+    pub fn confirm(&self, tx: &PlannedTx) -> anyhow::Result<()> {
+        match self {
+            Self::Auto => {
+                tracing::info!("sending transaction: {tx}");
+                Ok(())
+            }
+            Self::Interactive => {
+                eprintln!("{tx}");
+                eprint!("Send this transaction? [y/N] ");
+                stderr().flush()?;
+                let mut answer = String::new();
+                stdin().read_line(&mut answer)?;
+                ensure!(
+                    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+                    "{} {} not confirmed, aborting",
+                    tx.contract,
+                    tx.function
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How final a deployment transaction must be before the deployer relies on the contract.
+///
+/// Waiting for more than a single confirmation protects the deployment manifest from referencing
+/// contracts which disappear in an L1 reorg.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Finality {
+    /// Wait until the transaction has this many confirmations (1 means included in a block).
+    Confirmations(usize),
+    /// Wait until the block including the transaction is finalized.
+    Finalized,
+}
+
+impl Default for Finality {
+    fn default() -> Self {
+        Self::Confirmations(1)
+    }
+}
+
+impl Finality {
+    /// The finality to use on chain `chain_id` if none is configured.
+    ///
+    /// Deployments to Ethereum mainnet wait for finalization. Everywhere else, including testnets
+    /// and local dev chains, a single confirmation is enough by default.
+    pub fn default_for_chain(chain_id: u64) -> Self {
+        match chain_id {
+            1 => Self::Finalized,
+            _ => Self::default(),
+        }
+    }
+}
+
+impl fmt::Display for Finality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Confirmations(n) => write!(f, "{n} confirmations"),
+            Self::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+impl FromStr for Finality {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s == "finalized" {
+            return Ok(Self::Finalized);
+        }
+        let n = s.parse().with_context(|| {
+            format!("expected `finalized` or a number of confirmations, got {s}")
+        })?;
+        ensure!(n > 0, "number of confirmations must be at least 1");
+        Ok(Self::Confirmations(n))
+    }
+}
+
+impl<'de> Deserialize<'de> for Finality {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Accept `finality = 3` as well as `finality = "finalized"`.
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Confirmations(usize),
+            Tag(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Confirmations(0) => Err(de::Error::custom(
+                "number of confirmations must be at least 1",
+            )),
+            Repr::Confirmations(n) => Ok(Self::Confirmations(n)),
+            Repr::Tag(s) => s.parse().map_err(de::Error::custom),
+        }
+    }
+}
+
+/// Wait until the transaction with `receipt` reaches `finality`.
+///
+/// Returns the receipt of the transaction as of the time it reached `finality`, which differs from
+/// `receipt` if the transaction was reorged into a different block while waiting. Fails if the
+/// transaction was dropped from the chain altogether.
+pub async fn wait_for_finality<M: Middleware>(
+    l1: &M,
+    mut receipt: TransactionReceipt,
+    finality: Finality,
+) -> anyhow::Result<TransactionReceipt> {
+    // Receipts are only returned once the transaction has one confirmation.
+    if finality == Finality::Confirmations(1) {
+        return Ok(receipt);
+    }
+
+    let hash = receipt.transaction_hash;
+    loop {
+        let block = receipt
+            .block_number
+            .context("receipt is missing block number")?
+            .as_u64();
+        let reached = match finality {
+            Finality::Confirmations(n) => {
+                l1.get_block_number().await?.as_u64() + 1 >= block + n as u64
+            }
+            Finality::Finalized => {
+                let finalized = l1
+                    .get_block(BlockNumber::Finalized)
+                    .await?
+                    .and_then(|block| block.number)
+                    .map(|number| number.as_u64());
+                finalized >= Some(block)
+            }
+        };
+        if reached {
+            // Make sure the transaction is still in the block we have been waiting on.
+            let current = l1
+                .get_transaction_receipt(hash)
+                .await?
+                .with_context(|| format!("transaction {hash:#x} was dropped by an L1 reorg"))?;
+            if current.block_hash == receipt.block_hash {
+                return Ok(current);
+            }
+            tracing::warn!("transaction {hash:#x} was reorged into a different block");
+            receipt = current;
+            continue;
+        }
+
+        tracing::info!("waiting for transaction {hash:#x} in block {block} to be {finality}");
+        sleep(FINALITY_POLL_INTERVAL).await;
+    }
+}
+
+/// How often to check whether a transaction has reached the desired finality.
+const FINALITY_POLL_INTERVAL: Duration = Duration::from_secs(6);
+
+/// Decode the constructor arguments appended to `init_code`.
+///
+/// The boundary between the bytecode and the arguments is not recorded in the deploy transaction,
+/// so we look for the shortest suffix which is exactly the encoding of some arguments of the
+/// constructor's types.
+fn decode_constructor_args(inputs: &[Param], init_code: &[u8]) -> Vec<(String, String)> {
+    if inputs.is_empty() {
+        return vec![];
+    }
+    let types = inputs.iter().map(|p| p.kind.clone()).collect::<Vec<_>>();
+    for len in (32..=init_code.len()).step_by(32) {
+        let suffix = &init_code[init_code.len() - len..];
+        if let Ok(tokens) = abi::decode(&types, suffix) {
+            if abi::encode(&tokens) == suffix {
+                return named_args(inputs, tokens);
+            }
+        }
+    }
+    vec![("arguments".into(), "unable to decode".into())]
+}
+
+fn named_args(inputs: &[Param], tokens: Vec<Token>) -> Vec<(String, String)> {
+    inputs
+        .iter()
+        .zip(tokens)
+        .map(|(param, token)| (param.name.clone(), format_token(&token)))
+        .collect()
+}
+
+/// Format an ABI value the way it would be written in Solidity.
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(addr) => format!("{addr:#x}"),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => Bytes::from(bytes.clone()).to_string(),
+        Token::Uint(n) => n.to_string(),
+        Token::Int(n) => I256::from_raw(*n).to_string(),
+        Token::Bool(b) => b.to_string(),
+        Token::String(s) => format!("{s:?}"),
+        Token::Array(tokens) | Token::FixedArray(tokens) => format!(
+            "[{}]",
+            tokens
+                .iter()
+                .map(format_token)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        Token::Tuple(tokens) => format!(
+            "({})",
+            tokens
+                .iter()
+                .map(format_token)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
 /// Cache of contracts predeployed or deployed during this current run.
 #[derive(Debug, Clone, Default)]
-pub struct Contracts(HashMap<Contract, Address>);
+pub struct Contracts {
+    addresses: HashMap<Contract, Address>,
+    /// Receipts for the contracts deployed during this run.
+    ///
+    /// Predeployed contracts have an address but no receipt.
+    receipts: HashMap<Contract, TransactionReceipt>,
+    /// Size limits checked before sending each deploy transaction.
+    bytecode_limits: BytecodeLimits,
+    /// How transactions are approved before they are sent.
+    confirmation: Confirmation,
+    /// How final deploy transactions must be before a contract is recorded as deployed.
+    finality: Finality,
+}
 
 impl From<DeployedContracts> for Contracts {
     fn from(deployed: DeployedContracts) -> Self {
@@ -80,11 +539,37 @@ impl From<DeployedContracts> for Contracts {
         if let Some(addr) = deployed.light_client_proxy {
             m.insert(Contract::LightClientProxy, addr);
         }
-        Self(m)
+        Self {
+            addresses: m,
+            ..Default::default()
+        }
     }
 }
 
 impl Contracts {
+    /// Enforce the contract size limits of a chain with non-standard limits.
+    pub fn with_bytecode_limits(mut self, limits: BytecodeLimits) -> Self {
+        self.bytecode_limits = limits;
+        self
+    }
+
+    /// Ask the operator to approve each transaction before it is sent.
+    pub fn with_confirmation(mut self, confirmation: Confirmation) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// How transactions are approved before they are sent.
+    pub fn confirmation(&self) -> Confirmation {
+        self.confirmation
+    }
+
+    /// Wait for each deploy transaction to reach `finality` before recording the contract.
+    pub fn with_finality(mut self, finality: Finality) -> Self {
+        self.finality = finality;
+        self
+    }
+
     /// Deploy a contract by calling a function.
     ///
     /// The `deploy` function will be called only if contract `name` is not already deployed;
@@ -96,7 +581,7 @@ impl Contracts {
         name: Contract,
         deploy: impl FnOnce(&mut Self) -> BoxFuture<'_, anyhow::Result<Address>>,
     ) -> anyhow::Result<Address> {
-        if let Some(addr) = self.0.get(&name) {
+        if let Some(addr) = self.addresses.get(&name) {
             tracing::info!("skipping deployment of {name}, already deployed at {addr:#x}");
             return Ok(*addr);
         }
@@ -104,7 +589,7 @@ impl Contracts {
         let addr = deploy(self).await?;
         tracing::info!("deployed {name} at {addr:#x}");
 
-        self.0.insert(name, addr);
+        self.addresses.insert(name, addr);
         Ok(addr)
     }
 
@@ -123,9 +608,25 @@ impl Contracts {
             + Send
             + 'static,
     {
-        self.deploy_fn(name, |_| {
-            async {
-                let contract = tx.send().await?;
+        self.deploy_fn(name, |contracts| {
+            async move {
+                // Check the actual init code, including constructor arguments, so we don't burn gas
+                // on a transaction which is bound to fail.
+                if let Some(init_code) = tx.deployer.tx.data() {
+                    contracts.bytecode_limits.check_init_code(name, init_code)?;
+                }
+                let planned = PlannedTx::deployment(
+                    format!("{name:?}"),
+                    tx.abi(),
+                    &tx.deployer.tx,
+                    tx.client(),
+                )
+                .await;
+                contracts.confirmation.confirm(&planned)?;
+                let (contract, receipt) = tx.send_with_receipt().await?;
+                let receipt =
+                    wait_for_finality(contract.client_ref(), receipt, contracts.finality).await?;
+                contracts.receipts.insert(name, receipt);
                 Ok(contract.address())
             }
             .boxed()
@@ -133,15 +634,575 @@ impl Contracts {
         .await
     }
 
+    /// The address of contract `name`, if it is predeployed or has been deployed in this run.
+    pub fn address(&self, name: Contract) -> Option<Address> {
+        self.addresses.get(&name).copied()
+    }
+
+    /// The receipt of the transaction which deployed `name` during this run, if any.
+    pub fn receipt(&self, name: Contract) -> Option<&TransactionReceipt> {
+        self.receipts.get(&name)
+    }
+
     /// Write a .env file.
     pub fn write(&self, mut w: impl Write) -> anyhow::Result<()> {
-        for (contract, address) in &self.0 {
+        for (contract, address) in &self.addresses {
             writeln!(w, "{contract}={address:#x}")?;
         }
         Ok(())
     }
 }
 
+/// Gas spent by the transactions of a deployment run.
+///
+/// This can be serialized as JSON for cost tracking across environments, or displayed as a table.
+/// Gas prices and costs are denominated in wei.
+///
+/// Values the L1 did not report in a transaction's receipt are left out, rather than counted as 0.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GasReport {
+    pub entries: Vec<GasReportEntry>,
+    pub total_gas_used: U256,
+    pub total_cost: U256,
+}
+
+/// Gas spent by a single transaction.
+#[derive(Clone, Debug, Serialize)]
+pub struct GasReportEntry {
+    pub contract: Contract,
+    pub tx_hash: H256,
+    pub gas_used: Option<U256>,
+    pub effective_gas_price: Option<U256>,
+    pub cost: Option<U256>,
+}
+
+impl fmt::Display for GasReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<18} {:>12} {:>16} {:>24}",
+            "contract", "gas used", "gas price (gwei)", "cost (ETH)"
+        )?;
+        let show = |value: Option<String>| value.unwrap_or_else(|| "-".into());
+        for entry in &self.entries {
+            let gas_price = entry
+                .effective_gas_price
+                .map(|price| ethers::utils::format_units(price, "gwei"))
+                .transpose()
+                .map_err(|_| fmt::Error)?;
+            writeln!(
+                f,
+                "{:<18} {:>12} {:>16} {:>24}",
+                format!("{:?}", entry.contract),
+                show(entry.gas_used.map(|gas| gas.to_string())),
+                show(gas_price),
+                show(entry.cost.map(ethers::utils::format_ether)),
+            )?;
+        }
+        write!(
+            f,
+            "{:<18} {:>12} {:>16} {:>24}",
+            "total",
+            self.total_gas_used,
+            "",
+            ethers::utils::format_ether(self.total_cost),
+        )
+    }
+}
+
+/// A human-readable summary of a deployment run.
+///
+/// The report lists every contract known to the run, whether it was deployed or reused, its
+/// version and owner (for upgradable contracts), the deployment transaction and the gas spent. It
+/// is rendered as markdown so it can be pasted directly into release notes.
+#[derive(Clone, Debug)]
+pub struct DeploymentReport {
+    chain_id: u64,
+    deployer: Address,
+    explorer_url: Option<Url>,
+    entries: Vec<ReportEntry>,
+    manual_steps: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct ReportEntry {
+    contract: Contract,
+    address: Address,
+    receipt: Option<TransactionReceipt>,
+    version: Option<(u8, u8, u8)>,
+    owner: Option<Address>,
+}
+
+impl DeploymentReport {
+    /// Collect a report for `contracts`, querying on-chain metadata through `l1`.
+    pub async fn collect<M: Middleware + 'static>(
+        l1: Arc<M>,
+        contracts: &Contracts,
+        deployer: Address,
+    ) -> anyhow::Result<Self> {
+        let chain_id = l1.get_chainid().await?.as_u64();
+        let mut entries = vec![];
+        for (&contract, &address) in &contracts.addresses {
+            let (version, owner) = match contract {
+                // Only the proxy holds the upgradable contract's storage, but the version is a
+                // pure function, so we can query it on the implementation as well.
+                Contract::LightClient | Contract::LightClientProxy => {
+                    let light_client = LightClient::new(address, l1.clone());
+                    let version = light_client.get_version().call().await.ok();
+                    let owner = light_client
+                        .owner()
+                        .call()
+                        .await
+                        .ok()
+                        .filter(|owner| !owner.is_zero());
+                    (version, owner)
+                }
+                _ => (None, None),
+            };
+            entries.push(ReportEntry {
+                contract,
+                address,
+                receipt: contracts.receipt(contract).cloned(),
+                version,
+                owner,
+            });
+        }
+        entries.sort_by_key(|entry| entry.contract);
+
+        Ok(Self {
+            chain_id,
+            deployer,
+            explorer_url: None,
+            entries,
+            manual_steps: vec![],
+        })
+    }
+
+    /// Link transactions to a block explorer rooted at `url`.
+    pub fn with_explorer(mut self, url: Url) -> Self {
+        self.explorer_url = Some(url);
+        self
+    }
+
+    /// Record a step which must be completed by hand after this run.
+    pub fn manual_step(&mut self, step: impl Into<String>) {
+        self.manual_steps.push(step.into());
+    }
+
+    /// Total gas used by all transactions sent during this run.
+    pub fn total_gas_used(&self) -> U256 {
+        self.entries
+            .iter()
+            .filter_map(|entry| entry.receipt.as_ref()?.gas_used)
+            .fold(U256::zero(), |total, gas| total + gas)
+    }
+
+    /// Total fees, in wei, paid for all transactions sent during this run.
+    pub fn total_cost(&self) -> U256 {
+        self.entries
+            .iter()
+            .filter_map(|entry| tx_cost(entry.receipt.as_ref()?))
+            .fold(U256::zero(), |total, cost| total + cost)
+    }
+
+    /// Summarize the gas spent on transactions sent during this run.
+    pub fn gas_report(&self) -> GasReport {
+        let entries = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let receipt = entry.receipt.as_ref()?;
+                Some(GasReportEntry {
+                    contract: entry.contract,
+                    tx_hash: receipt.transaction_hash,
+                    gas_used: receipt.gas_used,
+                    effective_gas_price: receipt.effective_gas_price,
+                    cost: tx_cost(receipt),
+                })
+            })
+            .collect();
+        GasReport {
+            entries,
+            total_gas_used: self.total_gas_used(),
+            total_cost: self.total_cost(),
+        }
+    }
+
+    fn tx_link(&self, hash: H256) -> String {
+        match &self.explorer_url {
+            Some(url) => format!(
+                "[{hash:#x}]({}/tx/{hash:#x})",
+                url.as_str().trim_end_matches('/')
+            ),
+            None => format!("`{hash:#x}`"),
+        }
+    }
+
+    /// Write the report as markdown.
+    pub fn write(&self, mut w: impl Write) -> anyhow::Result<()> {
+        writeln!(w, "# Deployment report")?;
+        writeln!(w)?;
+        writeln!(w, "- Chain ID: {}", self.chain_id)?;
+        writeln!(w, "- Deployer: `{:#x}`", self.deployer)?;
+        writeln!(w, "- Total gas used: {}", self.total_gas_used())?;
+        writeln!(
+            w,
+            "- Total cost: {} ETH",
+            ethers::utils::format_ether(self.total_cost())
+        )?;
+        writeln!(w)?;
+
+        writeln!(w, "## Contracts")?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "| Contract | Address | Status | Version | Owner | Transaction | Gas used |"
+        )?;
+        writeln!(w, "|---|---|---|---|---|---|---|")?;
+        for entry in &self.entries {
+            let status = if entry.receipt.is_some() {
+                "deployed"
+            } else {
+                "predeployed"
+            };
+            let version = match entry.version {
+                Some((major, minor, patch)) => format!("{major}.{minor}.{patch}"),
+                None => "-".into(),
+            };
+            let owner = match entry.owner {
+                Some(owner) => format!("`{owner:#x}`"),
+                None => "-".into(),
+            };
+            let (tx, gas) = match &entry.receipt {
+                Some(receipt) => (
+                    self.tx_link(receipt.transaction_hash),
+                    receipt
+                        .gas_used
+                        .map(|gas| gas.to_string())
+                        .unwrap_or_else(|| "-".into()),
+                ),
+                None => ("-".into(), "-".into()),
+            };
+            writeln!(
+                w,
+                "| {:?} | `{:#x}` | {status} | {version} | {owner} | {tx} | {gas} |",
+                entry.contract, entry.address,
+            )?;
+        }
+
+        if !self.manual_steps.is_empty() {
+            writeln!(w)?;
+            writeln!(w, "## Outstanding manual steps")?;
+            writeln!(w)?;
+            for step in &self.manual_steps {
+                writeln!(w, "- [ ] {step}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The balance of `account` in the fee contract at `fee_proxy`.
+pub async fn fee_balance<M: Middleware + 'static>(
+    l1: Arc<M>,
+    fee_proxy: Address,
+    account: Address,
+) -> anyhow::Result<U256> {
+    Ok(FeeContract::new(fee_proxy, l1)
+        .balances(account)
+        .call()
+        .await?)
+}
+
+/// Deposit `amount` wei into the fee contract at `fee_proxy`, crediting `recipient`.
+///
+/// The deposit is paid by the signer of `l1`. The amount is checked against the contract's
+/// deposit limits before sending, and after the deposit is mined the receipt is checked for the
+/// corresponding `Deposit` event and the balance of `recipient` for the deposited amount.
+pub async fn deposit_fee<M: Middleware + 'static>(
+    l1: Arc<M>,
+    fee_proxy: Address,
+    amount: U256,
+    recipient: Address,
+) -> anyhow::Result<TransactionReceipt>
+where
+    M::Provider: Clone,
+{
+    let fee_contract = FeeContract::new(fee_proxy, l1.clone());
+    let min = fee_contract.min_deposit_amount().call().await?;
+    let max = fee_contract.max_deposit_amount().call().await?;
+    ensure!(
+        min <= amount && amount <= max,
+        "deposit of {amount} wei is outside the allowed range [{min}, {max}]"
+    );
+
+    let balance = fee_contract.balances(recipient).call().await?;
+    let (receipt, block) = crate::contract_send::<_, _, FeeContractErrors>(
+        &fee_contract.deposit(recipient).value(amount),
+    )
+    .await
+    .with_context(|| format!("depositing {amount} wei for {recipient:#x}"))?;
+    tracing::info!("deposited {amount} wei for {recipient:#x} in block {block}");
+
+    let deposited = receipt
+        .logs
+        .iter()
+        .filter_map(|log| parse_log::<DepositFilter>(log.clone()).ok())
+        .any(|event| event.user == recipient && event.amount == amount);
+    ensure!(
+        deposited,
+        "deposit transaction {:#x} did not emit a Deposit event",
+        receipt.transaction_hash
+    );
+    let new_balance = fee_contract.balances(recipient).call().await?;
+    ensure!(
+        new_balance == balance + amount,
+        "balance of {recipient:#x} is {new_balance} after deposit, expected {}",
+        balance + amount
+    );
+    Ok(receipt)
+}
+
+/// Expected configuration of an initialized light client proxy.
+#[derive(Clone, Copy, Debug)]
+pub struct LightClientExpectations {
+    pub owner: Address,
+    pub blocks_per_epoch: u32,
+}
+
+/// Assert that the light client behind `proxy` is configured as expected.
+///
+/// If Multicall3 is deployed on the L1, all of the reads are batched into a single `eth_call`,
+/// which matters on slow networks. Otherwise they are made one at a time. State-changing
+/// configuration calls cannot be batched the same way, since the light client's setters are
+/// restricted to its owner and calls made through Multicall3 have the multicall contract as the
+/// sender.
+pub async fn check_light_client_config<M: Middleware + 'static>(
+    l1: Arc<M>,
+    proxy: Address,
+    expected: LightClientExpectations,
+) -> anyhow::Result<()> {
+    let light_client = LightClient::new(proxy, l1.clone());
+    let (owner, blocks_per_epoch, version) = match Multicall::new(l1.clone(), None).await {
+        Ok(mut multicall) => {
+            multicall
+                .add_call(light_client.owner(), false)
+                .add_call(light_client.blocks_per_epoch(), false)
+                .add_call(light_client.get_version(), false);
+            multicall
+                .call::<(Address, u32, (u8, u8, u8))>()
+                .await
+                .context("error checking light client config")?
+        }
+        Err(err) => {
+            tracing::info!(
+                "multicall not available ({err}), checking light client config sequentially"
+            );
+            (
+                light_client.owner().call().await?,
+                light_client.blocks_per_epoch().call().await?,
+                light_client.get_version().call().await?,
+            )
+        }
+    };
+
+    ensure!(
+        owner == expected.owner,
+        "light client owner is {owner:#x}, expected {:#x}",
+        expected.owner
+    );
+    ensure!(
+        blocks_per_epoch == expected.blocks_per_epoch,
+        "light client has {blocks_per_epoch} blocks per epoch, expected {}",
+        expected.blocks_per_epoch
+    );
+    let (major, minor, patch) = version;
+    tracing::info!("light client v{major}.{minor}.{patch} at {proxy:#x} is configured correctly");
+    Ok(())
+}
+
+/// A change of ownership of a single contract.
+#[derive(Clone, Debug, Serialize)]
+pub struct OwnershipTransfer {
+    pub contract: Contract,
+    pub address: Address,
+    pub current_owner: Address,
+    pub new_owner: Address,
+}
+
+impl fmt::Display for OwnershipTransfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} ({:#x}): {:#x} -> {:#x}",
+            self.contract, self.address, self.current_owner, self.new_owner
+        )
+    }
+}
+
+/// Transfer ownership of every owned contract in `contracts` to `new_owner`.
+///
+/// Each contract's ownership model is determined by querying `owner()`: contracts which do not
+/// implement `Ownable` (such as libraries and `HotShot.sol`), or which have no owner (such as
+/// uninitialized implementations behind a proxy), are skipped, as are contracts already owned by
+/// `new_owner`. The transfers which apply are returned, so callers can show a diff of current vs.
+/// target owners.
+///
+/// Ownership is transferred using the signer of `l1`, which must be the current owner of each
+/// contract; if it is not, this fails before any transfer is planned or sent. If `dry_run` is set,
+/// no transactions are sent. Otherwise the new owner is checked after each transfer. `new_owner`
+/// should be a contract (such as a timelock or multisig) or an account able to manage the
+/// contracts, since `Ownable` transfers take effect immediately.
+pub async fn transfer_all_ownership<M: Middleware + 'static>(
+    l1: Arc<M>,
+    contracts: &Contracts,
+    new_owner: Address,
+    dry_run: bool,
+) -> anyhow::Result<Vec<OwnershipTransfer>>
+where
+    M::Provider: Clone,
+{
+    ensure!(
+        !new_owner.is_zero(),
+        "refusing to transfer ownership to the zero address"
+    );
+
+    let mut addresses = contracts.addresses.iter().collect::<Vec<_>>();
+    addresses.sort();
+    let mut transfers = vec![];
+    for (&contract, &address) in addresses {
+        // All of our upgradable contracts inherit `OwnableUpgradeable`, so the light client
+        // bindings can be used for any of them.
+        let ownable = LightClient::new(address, l1.clone());
+        let current_owner = match ownable.owner().call().await {
+            Ok(owner) => owner,
+            // A contract without `owner()` reverts, or returns nothing if it has a fallback.
+            Err(err)
+                if err.is_revert()
+                    || matches!(
+                        err,
+                        ContractError::AbiError(_) | ContractError::DecodingError(_)
+                    ) =>
+            {
+                tracing::info!("{contract:?} at {address:#x} is not ownable, skipping");
+                continue;
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("querying the owner of {contract:?}"))
+            }
+        };
+        if current_owner.is_zero() {
+            tracing::info!("{contract:?} at {address:#x} has no owner, skipping");
+            continue;
+        }
+        if current_owner == new_owner {
+            tracing::info!("{contract:?} at {address:#x} is already owned by {new_owner:#x}");
+            continue;
+        }
+        transfers.push(OwnershipTransfer {
+            contract,
+            address,
+            current_owner,
+            new_owner,
+        });
+    }
+
+    if let Some(sender) = l1.default_sender() {
+        for transfer in &transfers {
+            ensure!(
+                sender == transfer.current_owner,
+                "cannot transfer ownership of {:?}: owned by {:#x}, not the signer {sender:#x}",
+                transfer.contract,
+                transfer.current_owner,
+            );
+        }
+    }
+
+    for transfer in &transfers {
+        tracing::info!("ownership transfer: {transfer}");
+    }
+    if dry_run {
+        return Ok(transfers);
+    }
+
+    for transfer in &transfers {
+        let ownable = LightClient::new(transfer.address, l1.clone());
+        let call = ownable.transfer_ownership(new_owner);
+        let planned = PlannedTx::call(format!("{:?}", transfer.contract), &call).await;
+        contracts.confirmation.confirm(&planned)?;
+        crate::contract_send::<_, _, LightClientErrors>(&call)
+            .await
+            .with_context(|| format!("transferring ownership of {:?}", transfer.contract))?;
+        let owner = ownable.owner().call().await?;
+        ensure!(
+            owner == new_owner,
+            "ownership of {:?} was not transferred: owner is {owner:#x}",
+            transfer.contract
+        );
+        tracing::info!(
+            "transferred ownership of {:?} to {new_owner:#x}",
+            transfer.contract
+        );
+    }
+    Ok(transfers)
+}
+
+/// An implementation which a proxy has been upgraded to.
+#[derive(Clone, Debug, Serialize)]
+pub struct ImplementationRecord {
+    /// Address of the implementation contract.
+    pub implementation: Address,
+    /// Block in which the proxy was pointed at this implementation.
+    pub block: u64,
+    /// Transaction which pointed the proxy at this implementation.
+    pub tx_hash: H256,
+    /// Version reported by the implementation's `getVersion`, if it has one.
+    pub version: Option<(u8, u8, u8)>,
+}
+
+/// Reconstruct the implementation history of an ERC1967 proxy.
+///
+/// This scans `Upgraded` events emitted by `proxy` starting from `from_block`. Since the proxy
+/// constructor also emits `Upgraded`, scanning from the deployment block (or genesis) yields the
+/// full upgrade trail, oldest first.
+pub async fn implementation_history<M: Middleware + 'static>(
+    l1: Arc<M>,
+    proxy: Address,
+    from_block: u64,
+) -> anyhow::Result<Vec<ImplementationRecord>> {
+    let mut events = ERC1967Proxy::new(proxy, l1.clone())
+        .upgraded_filter()
+        .from_block(from_block)
+        .query_with_meta()
+        .await
+        .context("error fetching Upgraded events")?;
+    // Order upgrades by their position in the chain, which is the order they took effect.
+    events.sort_by_key(|(_, meta)| (meta.block_number, meta.log_index));
+
+    let mut history = vec![];
+    for (event, meta) in events {
+        // All of our upgradable contracts expose `getVersion` with the same signature, so the light
+        // client bindings can be used to query it on any implementation.
+        let version = LightClient::new(event.implementation, l1.clone())
+            .get_version()
+            .call()
+            .await
+            .ok();
+        history.push(ImplementationRecord {
+            implementation: event.implementation,
+            block: meta.block_number.as_u64(),
+            tx_hash: meta.transaction_hash,
+            version,
+        });
+    }
+    Ok(history)
+}
+
+/// The fee paid for a mined transaction, if the receipt includes enough information to compute it.
+fn tx_cost(receipt: &TransactionReceipt) -> Option<U256> {
+    Some(receipt.gas_used? * receipt.effective_gas_price?)
+}
+
 /// Default deployment function `LightClient.sol` in production
 ///
 /// # NOTE:
@@ -191,16 +1252,28 @@ pub async fn deploy_light_client_contract<M: Middleware + 'static>(
         .context("error linking LightClientStateUpdateVK lib")?;
     ensure!(!bytecode.is_unlinked(), "failed to link LightClient.sol");
 
+    let init_code = bytecode
+        .as_bytes()
+        .context("error parsing bytecode for linked LightClient contract")?
+        .clone();
+    contracts
+        .bytecode_limits
+        .check_init_code(Contract::LightClient, &init_code)?;
+
     // Deploy light client.
-    let light_client_factory = ContractFactory::new(
-        LIGHTCLIENT_ABI.clone(),
-        bytecode
-            .as_bytes()
-            .context("error parsing bytecode for linked LightClient contract")?
-            .clone(),
-        l1,
-    );
-    let contract = light_client_factory.deploy(())?.send().await?;
+    let light_client_factory = ContractFactory::new(LIGHTCLIENT_ABI.clone(), init_code, l1);
+    let tx = light_client_factory.deploy(())?;
+    let planned = PlannedTx::deployment(
+        format!("{:?}", Contract::LightClient),
+        tx.abi(),
+        &tx.tx,
+        tx.client(),
+    )
+    .await;
+    contracts.confirmation.confirm(&planned)?;
+    let (contract, receipt) = tx.send_with_receipt().await?;
+    let receipt = wait_for_finality(contract.client_ref(), receipt, contracts.finality).await?;
+    contracts.receipts.insert(Contract::LightClient, receipt);
     Ok(contract.address())
 }
 
@@ -251,22 +1324,553 @@ pub async fn deploy_mock_light_client_contract<M: Middleware + 'static>(
         "failed to link LightClientMock.sol"
     );
 
+    let init_code = bytecode
+        .as_bytes()
+        .context("error parsing bytecode for linked LightClientMock contract")?
+        .clone();
+    contracts
+        .bytecode_limits
+        .check_init_code(Contract::LightClient, &init_code)?;
+
     // Deploy light client.
-    let light_client_factory = ContractFactory::new(
-        LIGHTCLIENTMOCK_ABI.clone(),
-        bytecode
-            .as_bytes()
-            .context("error parsing bytecode for linked LightClientMock contract")?
-            .clone(),
-        l1,
-    );
+    let light_client_factory = ContractFactory::new(LIGHTCLIENTMOCK_ABI.clone(), init_code, l1);
     let constructor_args = match constructor_args {
         Some(args) => args,
         None => (ParsedLightClientState::dummy_genesis().into(), u32::MAX),
     };
-    let contract = light_client_factory
-        .deploy(constructor_args)?
-        .send()
-        .await?;
+    let tx = light_client_factory.deploy(constructor_args)?;
+    let planned = PlannedTx::deployment(
+        format!("{:?}", Contract::LightClient),
+        tx.abi(),
+        &tx.tx,
+        tx.client(),
+    )
+    .await;
+    contracts.confirmation.confirm(&planned)?;
+    let (contract, receipt) = tx.send_with_receipt().await?;
+    let receipt = wait_for_finality(contract.client_ref(), receipt, contracts.finality).await?;
+    contracts.receipts.insert(Contract::LightClient, receipt);
     Ok(contract.address())
 }
+
+/// A stable, high-level interface for deploying the sequencer contracts.
+///
+/// The free functions in this module are the building blocks of a deployment, and their signatures
+/// change as the deployment process evolves. Tools which embed the deployer (test harnesses, local
+/// development networks, provisioning scripts) should use this type instead, which bundles the L1
+/// client and the contracts cache and exposes each step of a deployment as a single method.
+///
+/// ```no_run
+/// # use async_std::sync::Arc;
+/// # use contract_bindings::shared_types::LightClientState;
+/// # use ethers::prelude::*;
+/// # use sequencer_utils::deployer::Deployer;
+/// # async fn example<M: Middleware + 'static>(
+/// #     l1: Arc<M>,
+/// #     genesis: LightClientState,
+/// #     owner: Address,
+/// # ) -> anyhow::Result<()> {
+/// let mut deployer = Deployer::builder(l1).build().await?;
+/// deployer.deploy_hotshot().await?;
+/// let light_client = deployer.deploy_light_client(genesis, 100, owner).await?;
+/// deployer.contracts().write(std::io::stdout())?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Deployer<M> {
+    l1: Arc<M>,
+    contracts: Contracts,
+}
+
+/// Builder for a [`Deployer`].
+#[derive(Debug)]
+pub struct DeployerBuilder<M> {
+    l1: Arc<M>,
+    predeployed: DeployedContracts,
+    bytecode_limits: BytecodeLimits,
+    confirmation: Confirmation,
+    finality: Option<Finality>,
+}
+
+impl<M: Middleware + 'static> DeployerBuilder<M> {
+    /// Use already deployed contracts instead of deploying new ones.
+    pub fn predeployed(mut self, contracts: DeployedContracts) -> Self {
+        self.predeployed = contracts;
+        self
+    }
+
+    /// Enforce non-standard contract size limits.
+    pub fn bytecode_limits(mut self, limits: BytecodeLimits) -> Self {
+        self.bytecode_limits = limits;
+        self
+    }
+
+    /// How transactions are approved before they are sent.
+    ///
+    /// The default is [`Confirmation::Auto`].
+    pub fn confirmation(mut self, confirmation: Confirmation) -> Self {
+        self.confirmation = confirmation;
+        self
+    }
+
+    /// How final deploy transactions must be before a contract is recorded as deployed.
+    ///
+    /// The default depends on the chain; see [`Finality::default_for_chain`].
+    pub fn finality(mut self, finality: Finality) -> Self {
+        self.finality = Some(finality);
+        self
+    }
+
+    pub async fn build(self) -> anyhow::Result<Deployer<M>> {
+        let finality = match self.finality {
+            Some(finality) => finality,
+            None => {
+                let chain_id = self.l1.get_chainid().await.context("fetching chain ID")?;
+                Finality::default_for_chain(chain_id.as_u64())
+            }
+        };
+        tracing::info!("waiting for deploy transactions to be {finality}");
+
+        let contracts = Contracts::from(self.predeployed)
+            .with_bytecode_limits(self.bytecode_limits)
+            .with_confirmation(self.confirmation)
+            .with_finality(finality);
+        Ok(Deployer {
+            l1: self.l1,
+            contracts,
+        })
+    }
+}
+
+impl<M: Middleware + 'static> Deployer<M> {
+    /// Start building a deployer which sends transactions using `l1`.
+    pub fn builder(l1: Arc<M>) -> DeployerBuilder<M> {
+        DeployerBuilder {
+            l1,
+            predeployed: Default::default(),
+            bytecode_limits: Default::default(),
+            confirmation: Default::default(),
+            finality: None,
+        }
+    }
+
+    /// The L1 client used to send transactions.
+    pub fn l1(&self) -> &Arc<M> {
+        &self.l1
+    }
+
+    /// Contracts predeployed or deployed so far.
+    pub fn contracts(&self) -> &Contracts {
+        &self.contracts
+    }
+
+    /// Finish deploying, returning the deployed contracts.
+    pub fn into_contracts(self) -> Contracts {
+        self.contracts
+    }
+
+    /// Deploy `HotShot.sol`, unless it is predeployed.
+    pub async fn deploy_hotshot(&mut self) -> anyhow::Result<Address> {
+        self.contracts
+            .deploy_tx(Contract::HotShot, HotShot::deploy(self.l1.clone(), ())?)
+            .await
+    }
+
+    /// Deploy and initialize the upgradable light client, unless it is predeployed.
+    ///
+    /// This deploys the light client implementation and its libraries, then a proxy which is
+    /// initialized with `genesis`, `blocks_per_epoch` and `owner`. A newly deployed proxy is
+    /// checked to be configured as requested. Returns the address of the proxy.
+    pub async fn deploy_light_client(
+        &mut self,
+        genesis: LightClientState,
+        blocks_per_epoch: u32,
+        owner: Address,
+    ) -> anyhow::Result<Address> {
+        let l1 = self.l1.clone();
+        let implementation = self
+            .contracts
+            .deploy_fn(Contract::LightClient, |contracts| {
+                deploy_light_client_contract(l1.clone(), contracts).boxed()
+            })
+            .await?;
+
+        let data = LightClient::new(implementation, self.l1.clone())
+            .initialize(genesis, blocks_per_epoch, owner)
+            .calldata()
+            .context("calldata for initialize transaction not available")?;
+        let proxy = self
+            .contracts
+            .deploy_tx(
+                Contract::LightClientProxy,
+                ERC1967Proxy::deploy(self.l1.clone(), (implementation, data))?,
+            )
+            .await?;
+
+        // A predeployed proxy may have been initialized differently, so only check a new one.
+        if self.contracts.receipt(Contract::LightClientProxy).is_some() {
+            check_light_client_config(
+                self.l1.clone(),
+                proxy,
+                LightClientExpectations {
+                    owner,
+                    blocks_per_epoch,
+                },
+            )
+            .await?;
+        }
+        Ok(proxy)
+    }
+
+    /// Deploy `LightClientMock.sol`, unless a light client is predeployed.
+    ///
+    /// The mock is not upgradable and does not verify proofs. It is initialized with `genesis` and
+    /// blocks per epoch through its constructor, or with a dummy genesis state if `None`.
+    pub async fn deploy_mock_light_client(
+        &mut self,
+        genesis: Option<(LightClientState, u32)>,
+    ) -> anyhow::Result<Address> {
+        let l1 = self.l1.clone();
+        self.contracts
+            .deploy_fn(Contract::LightClient, |contracts| {
+                deploy_mock_light_client_contract(l1, contracts, genesis).boxed()
+            })
+            .await
+    }
+
+    /// Transfer ownership of all owned contracts to `new_owner`.
+    ///
+    /// See [`transfer_all_ownership`].
+    pub async fn transfer_ownership(
+        &self,
+        new_owner: Address,
+        dry_run: bool,
+    ) -> anyhow::Result<Vec<OwnershipTransfer>>
+    where
+        M::Provider: Clone,
+    {
+        transfer_all_ownership(self.l1.clone(), &self.contracts, new_owner, dry_run).await
+    }
+
+    /// Summarize the deployment so far, with `deployer` as the account which sent the transactions.
+    pub async fn report(&self, deployer: Address) -> anyhow::Result<DeploymentReport> {
+        DeploymentReport::collect(self.l1.clone(), &self.contracts, deployer).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{init_signer, AnvilOptions};
+
+    #[test]
+    fn test_bytecode_within_limits() {
+        BytecodeLimits::default().check_all(false).unwrap();
+        BytecodeLimits::default().check_all(true).unwrap();
+    }
+
+    #[test]
+    fn test_bytecode_limit_excess() {
+        let limits = BytecodeLimits {
+            max_runtime_code_size: 10,
+            max_init_code_size: 20,
+        };
+        limits.check_init_code(Contract::HotShot, &[0; 20]).unwrap();
+        limits
+            .check_runtime_code(Contract::HotShot, &[0; 10])
+            .unwrap();
+
+        let err = limits
+            .check_init_code(Contract::HotShot, &[0; 23])
+            .unwrap_err();
+        assert!(err.to_string().contains("by 3 bytes"), "{err}");
+        let err = limits
+            .check_runtime_code(Contract::HotShot, &[0; 11])
+            .unwrap_err();
+        assert!(err.to_string().contains("by 1 bytes"), "{err}");
+    }
+
+    #[test]
+    fn test_decode_constructor_args() {
+        let inputs = &ERC1967PROXY_ABI.constructor.as_ref().unwrap().inputs;
+        let implementation = Address::random();
+        let mut init_code = ERC1967PROXY_BYTECODE.to_vec();
+        init_code.extend(abi::encode(&[
+            Token::Address(implementation),
+            Token::Bytes(vec![1, 2, 3]),
+        ]));
+        assert_eq!(
+            decode_constructor_args(inputs, &init_code),
+            [
+                ("implementation".into(), format!("{implementation:#x}")),
+                ("_data".into(), "0x010203".into()),
+            ]
+        );
+
+        // Constructors without arguments have nothing to decode.
+        assert_eq!(decode_constructor_args(&[], &init_code), []);
+    }
+
+    #[test]
+    fn test_parse_finality() {
+        assert_eq!(
+            "finalized".parse::<Finality>().unwrap(),
+            Finality::Finalized
+        );
+        assert_eq!("3".parse::<Finality>().unwrap(), Finality::Confirmations(3));
+        "0".parse::<Finality>().unwrap_err();
+        "safe".parse::<Finality>().unwrap_err();
+
+        // Config files may give either a tag or a number.
+        let parse = serde_json::from_str::<Finality>;
+        assert_eq!(parse("\"finalized\"").unwrap(), Finality::Finalized);
+        assert_eq!(parse("12").unwrap(), Finality::Confirmations(12));
+        parse("0").unwrap_err();
+    }
+
+    #[async_std::test]
+    async fn test_deployer_reuses_predeployed() {
+        let anvil = AnvilOptions::default().spawn().await;
+        let l1 = Arc::new(
+            init_signer(
+                &anvil.url(),
+                "test test test test test test test test test test test junk",
+                0,
+            )
+            .await
+            .unwrap(),
+        );
+
+        let mut deployer = Deployer::builder(l1.clone()).build().await.unwrap();
+        let hotshot = deployer.deploy_hotshot().await.unwrap();
+        let light_client = deployer.deploy_mock_light_client(None).await.unwrap();
+        assert_eq!(
+            deployer.contracts().address(Contract::HotShot),
+            Some(hotshot)
+        );
+        assert!(deployer
+            .contracts()
+            .receipt(Contract::LightClient)
+            .is_some());
+        let gas = deployer.report(l1.address()).await.unwrap().gas_report();
+        // HotShot, and the mock light client with its libraries.
+        assert_eq!(gas.entries.len(), 4);
+        assert_eq!(
+            gas.entries
+                .iter()
+                .fold(U256::zero(), |total, entry| total + entry.cost.unwrap()),
+            gas.total_cost
+        );
+
+        // A deployer configured with existing contracts does not deploy them again.
+        let mut deployer = Deployer::builder(l1)
+            .predeployed(DeployedContracts {
+                hotshot: Some(hotshot),
+                light_client: Some(light_client),
+                ..Default::default()
+            })
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(deployer.deploy_hotshot().await.unwrap(), hotshot);
+        assert_eq!(
+            deployer.deploy_mock_light_client(None).await.unwrap(),
+            light_client
+        );
+        let contracts = deployer.into_contracts();
+        assert!(contracts.receipt(Contract::HotShot).is_none());
+        assert!(contracts.receipt(Contract::LightClient).is_none());
+    }
+
+    #[async_std::test]
+    async fn test_deployment_report() {
+        let anvil = AnvilOptions::default().spawn().await;
+        let l1 = Arc::new(
+            init_signer(
+                &anvil.url(),
+                "test test test test test test test test test test test junk",
+                0,
+            )
+            .await
+            .unwrap(),
+        );
+        let hotshot = Deployer::builder(l1.clone())
+            .build()
+            .await
+            .unwrap()
+            .deploy_hotshot()
+            .await
+            .unwrap();
+
+        let mut deployer = Deployer::builder(l1.clone())
+            .predeployed(DeployedContracts {
+                hotshot: Some(hotshot),
+                ..Default::default()
+            })
+            .build()
+            .await
+            .unwrap();
+        deployer.deploy_hotshot().await.unwrap();
+        let proxy = deployer
+            .deploy_light_client(
+                ParsedLightClientState::dummy_genesis().into(),
+                10,
+                l1.address(),
+            )
+            .await
+            .unwrap();
+        let proxy_tx = deployer
+            .contracts()
+            .receipt(Contract::LightClientProxy)
+            .unwrap()
+            .transaction_hash;
+
+        let mut report = deployer
+            .report(l1.address())
+            .await
+            .unwrap()
+            .with_explorer("https://explorer.example/".parse().unwrap());
+        report.manual_step("Fund the prover account");
+        let mut markdown = vec![];
+        report.write(&mut markdown).unwrap();
+        let markdown = String::from_utf8(markdown).unwrap();
+
+        assert!(
+            markdown.contains(&format!("- Deployer: `{:#x}`", l1.address())),
+            "{markdown}"
+        );
+        assert!(
+            markdown.contains(&format!("- Total gas used: {}", report.total_gas_used())),
+            "{markdown}"
+        );
+        // Predeployed contracts are listed without a transaction.
+        assert!(
+            markdown.contains(&format!(
+                "| HotShot | `{hotshot:#x}` | predeployed | - | - | - | - |"
+            )),
+            "{markdown}"
+        );
+        // The proxy reports its version and owner, and links its deploy transaction.
+        assert!(
+            markdown.contains(&format!(
+                "| LightClientProxy | `{proxy:#x}` | deployed | 1.0.0 | `{:#x}` | \
+                 [{proxy_tx:#x}](https://explorer.example/tx/{proxy_tx:#x}) |",
+                l1.address()
+            )),
+            "{markdown}"
+        );
+        assert!(
+            markdown.contains("## Outstanding manual steps\n\n- [ ] Fund the prover account\n"),
+            "{markdown}"
+        );
+    }
+
+    #[async_std::test]
+    async fn test_transfer_all_ownership() {
+        let anvil = AnvilOptions::default().spawn().await;
+        let mnemonic = "test test test test test test test test test test test junk";
+        let l1 = Arc::new(init_signer(&anvil.url(), mnemonic, 0).await.unwrap());
+        let other = Arc::new(init_signer(&anvil.url(), mnemonic, 1).await.unwrap());
+        let new_owner = Address::random();
+
+        let mut deployer = Deployer::builder(l1.clone()).build().await.unwrap();
+        deployer.deploy_hotshot().await.unwrap();
+        let proxy = deployer
+            .deploy_light_client(
+                ParsedLightClientState::dummy_genesis().into(),
+                10,
+                l1.address(),
+            )
+            .await
+            .unwrap();
+        let light_client = LightClient::new(proxy, l1.clone());
+
+        // Only the proxy is owned: HotShot and the libraries are not ownable, and the
+        // implementation behind the proxy has no owner. A dry run sends nothing.
+        let planned = deployer.transfer_ownership(new_owner, true).await.unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].contract, Contract::LightClientProxy);
+        assert_eq!(planned[0].current_owner, l1.address());
+        assert_eq!(light_client.owner().call().await.unwrap(), l1.address());
+
+        // A signer which does not own the contracts is rejected before anything is planned.
+        let err = transfer_all_ownership(other, deployer.contracts(), new_owner, true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not the signer"), "{err}");
+
+        let transferred = deployer.transfer_ownership(new_owner, false).await.unwrap();
+        assert_eq!(transferred.len(), 1);
+        assert_eq!(light_client.owner().call().await.unwrap(), new_owner);
+
+        // Contracts already owned by the new owner are skipped.
+        assert!(deployer
+            .transfer_ownership(new_owner, false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[async_std::test]
+    async fn test_implementation_history() {
+        let anvil = AnvilOptions::default().spawn().await;
+        let l1 = Arc::new(
+            init_signer(
+                &anvil.url(),
+                "test test test test test test test test test test test junk",
+                0,
+            )
+            .await
+            .unwrap(),
+        );
+
+        let mut deployer = Deployer::builder(l1.clone()).build().await.unwrap();
+        let proxy = deployer
+            .deploy_light_client(
+                ParsedLightClientState::dummy_genesis().into(),
+                10,
+                l1.address(),
+            )
+            .await
+            .unwrap();
+        let original = deployer.contracts().address(Contract::LightClient).unwrap();
+
+        // The proxy is deployed pointing at its first implementation.
+        let history = implementation_history(l1.clone(), proxy, 0).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].implementation, original);
+        assert_eq!(history[0].version, Some((1, 0, 0)));
+
+        // Upgrades are appended in order.
+        let upgraded = deploy_light_client_contract(l1.clone(), &mut Contracts::default())
+            .await
+            .unwrap();
+        let receipt = LightClient::new(proxy, l1.clone())
+            .upgrade_to_and_call(upgraded, Bytes::new())
+            .send()
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+            .unwrap();
+        let history = implementation_history(l1.clone(), proxy, 0).await.unwrap();
+        assert_eq!(
+            history
+                .iter()
+                .map(|record| record.implementation)
+                .collect::<Vec<_>>(),
+            [original, upgraded]
+        );
+        assert_eq!(history[1].tx_hash, receipt.transaction_hash);
+        assert_eq!(history[1].block, receipt.block_number.unwrap().as_u64());
+        assert!(history[0].block < history[1].block);
+
+        // Scanning from a later block only finds the later upgrades.
+        let history = implementation_history(l1, proxy, history[1].block)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].implementation, upgraded);
+    }
+}
@@ -21,6 +21,8 @@ use tempfile::TempDir;
 use url::Url;
 
 pub mod deployer;
+pub mod provider;
+pub mod signer;
 pub mod test_utils;
 
 pub type Signer = SignerMiddleware<Provider<Http>, LocalWallet>;
@@ -0,0 +1,138 @@
+//! Connecting to an L1 JSON-RPC endpoint over HTTP or WebSockets.
+//!
+//! [`L1Transport`] picks the transport based on the scheme of the endpoint URL, so tools which
+//! take an L1 URL can be pointed at `http(s)://` and `ws(s)://` endpoints alike. WebSocket
+//! connections are re-established automatically when they drop, and any active subscriptions are
+//! re-installed on the new connection, which keeps long-running monitors and receipt waits alive
+//! on flaky endpoints.
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use ethers::providers::{
+    Http, JsonRpcClient, Provider, ProviderError, PubsubClient, Ws, DEFAULT_POLL_INTERVAL,
+};
+use ethers::types::U256;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+use std::time::Duration;
+use url::Url;
+
+/// Default number of times a dropped WebSocket connection is re-established before giving up.
+pub const DEFAULT_WS_RECONNECTS: usize = 10;
+
+/// A JSON-RPC transport to the L1.
+#[derive(Clone, Debug)]
+pub enum L1Transport {
+    Http(Http),
+    Ws(Ws),
+}
+
+impl L1Transport {
+    /// Connect to `url`, using the transport given by its scheme.
+    ///
+    /// For WebSocket URLs, the connection is re-established up to `reconnects` times if it drops.
+    pub async fn connect(url: &Url, reconnects: usize) -> anyhow::Result<Self> {
+        match url.scheme() {
+            "http" | "https" => Ok(Self::Http(Http::new(url.clone()))),
+            "ws" | "wss" => {
+                let ws = Ws::connect_with_reconnects(url.as_str(), reconnects)
+                    .await
+                    .with_context(|| format!("connecting to {url}"))?;
+                Ok(Self::Ws(ws))
+            }
+            scheme => bail!("unsupported L1 provider scheme {scheme}"),
+        }
+    }
+
+    /// Whether this transport supports subscriptions.
+    pub fn is_pubsub(&self) -> bool {
+        matches!(self, Self::Ws(_))
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for L1Transport {
+    type Error = ProviderError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            Self::Http(http) => http.request(method, params).await.map_err(Into::into),
+            Self::Ws(ws) => ws.request(method, params).await.map_err(Into::into),
+        }
+    }
+}
+
+impl PubsubClient for L1Transport {
+    type NotificationStream = <Ws as PubsubClient>::NotificationStream;
+
+    fn subscribe<T: Into<U256>>(&self, id: T) -> Result<Self::NotificationStream, Self::Error> {
+        match self {
+            Self::Http(_) => Err(ProviderError::UnsupportedRPC),
+            Self::Ws(ws) => ws.subscribe(id).map_err(Into::into),
+        }
+    }
+
+    fn unsubscribe<T: Into<U256>>(&self, id: T) -> Result<(), Self::Error> {
+        match self {
+            Self::Http(_) => Err(ProviderError::UnsupportedRPC),
+            Self::Ws(ws) => ws.unsubscribe(id).map_err(Into::into),
+        }
+    }
+}
+
+/// Connect a provider to the L1 endpoint at `url`.
+///
+/// See [`L1Transport::connect`].
+pub async fn connect_l1(url: &Url, reconnects: usize) -> anyhow::Result<Provider<L1Transport>> {
+    let transport = L1Transport::connect(url, reconnects).await?;
+    // Requests over a WebSocket are cheap, so poll for receipts more often.
+    let interval = if transport.is_pubsub() {
+        Duration::from_secs(1)
+    } else {
+        DEFAULT_POLL_INTERVAL
+    };
+    Ok(Provider::new(transport).interval(interval))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::AnvilOptions;
+    use ethers::providers::Middleware;
+    use futures::StreamExt;
+
+    #[async_std::test]
+    async fn test_connect_l1() {
+        let anvil = AnvilOptions::default().spawn().await;
+
+        for url in [anvil.url(), anvil.ws_url()] {
+            let l1 = connect_l1(&url, DEFAULT_WS_RECONNECTS).await.unwrap();
+            assert_eq!(l1.get_chainid().await.unwrap(), 31337.into());
+        }
+
+        // Subscriptions only work over WebSockets.
+        let l1 = connect_l1(&anvil.ws_url(), DEFAULT_WS_RECONNECTS)
+            .await
+            .unwrap();
+        let mut blocks = l1.subscribe_blocks().await.unwrap();
+        l1.request::<_, serde_json::Value>("evm_mine", ())
+            .await
+            .unwrap();
+        blocks.next().await.unwrap();
+
+        let l1 = connect_l1(&anvil.url(), DEFAULT_WS_RECONNECTS)
+            .await
+            .unwrap();
+        l1.subscribe_blocks().await.unwrap_err();
+    }
+
+    #[async_std::test]
+    async fn test_connect_l1_bad_scheme() {
+        let url = "ftp://localhost:8545".parse().unwrap();
+        connect_l1(&url, DEFAULT_WS_RECONNECTS).await.unwrap_err();
+    }
+}
@@ -0,0 +1,207 @@
+//! Signing L1 transactions with keys held outside of the process.
+//!
+//! [`L1Signer`] implements the ethers [`Signer`] interface on top of a key derived from a
+//! mnemonic, a Ledger hardware wallet, or an AWS KMS key, so that services which control a funded
+//! L1 account need not be handed its private key. Hardware wallets and KMS keys are supported with
+//! the `ledger` and `aws-kms` features respectively.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use derive_more::{Display, From};
+use ethers::{
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Signer, WalletError},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Signature,
+    },
+};
+use std::sync::Arc;
+
+#[cfg(feature = "aws-kms")]
+use ethers::signers::{AwsSigner, AwsSignerError};
+#[cfg(feature = "ledger")]
+use ethers::signers::{HDPath, Ledger, LedgerError};
+
+/// Where the key for signing L1 transactions is held.
+#[derive(Clone, Debug)]
+pub enum L1SignerSource {
+    /// Account `index` derived from a mnemonic phrase.
+    Mnemonic { phrase: String, index: u32 },
+    /// Account `index` of a Ledger hardware wallet, using the Ledger Live derivation path.
+    #[cfg(feature = "ledger")]
+    Ledger { index: usize },
+    /// An AWS KMS key, accessed with the region and credentials configured in the environment.
+    #[cfg(feature = "aws-kms")]
+    AwsKms { key_id: String },
+}
+
+impl L1SignerSource {
+    /// Connect to the signer, for transactions on the chain with ID `chain_id`.
+    pub async fn connect(&self, chain_id: u64) -> anyhow::Result<L1Signer> {
+        let inner = match self {
+            Self::Mnemonic { phrase, index } => Inner::Local(
+                MnemonicBuilder::<English>::default()
+                    .phrase(phrase.as_str())
+                    .index(*index)
+                    .context("error building wallet")?
+                    .build()
+                    .context("error opening wallet")?,
+            ),
+            #[cfg(feature = "ledger")]
+            Self::Ledger { index } => Inner::Ledger(
+                Ledger::new(HDPath::LedgerLive(*index), chain_id)
+                    .await
+                    .context("error connecting to Ledger")?,
+            ),
+            #[cfg(feature = "aws-kms")]
+            Self::AwsKms { key_id } => {
+                let kms = rusoto_kms::KmsClient::new(rusoto_core::Region::default());
+                Inner::Aws(
+                    AwsSigner::new(kms, key_id, chain_id)
+                        .await
+                        .context("error loading KMS key")?,
+                )
+            }
+        };
+        Ok(L1Signer {
+            inner: Arc::new(inner),
+            chain_id,
+        })
+    }
+}
+
+/// A signer for L1 transactions.
+///
+/// Cloning the signer shares the underlying key or device, so one connection to a hardware wallet
+/// or KMS key can be used for several chains with [`Signer::with_chain_id`].
+#[derive(Clone, Debug)]
+pub struct L1Signer {
+    inner: Arc<Inner>,
+    chain_id: u64,
+}
+
+#[derive(Debug)]
+enum Inner {
+    Local(LocalWallet),
+    #[cfg(feature = "ledger")]
+    Ledger(Ledger),
+    #[cfg(feature = "aws-kms")]
+    Aws(AwsSigner),
+}
+
+impl From<LocalWallet> for L1Signer {
+    fn from(wallet: LocalWallet) -> Self {
+        Self {
+            chain_id: wallet.chain_id(),
+            inner: Arc::new(Inner::Local(wallet)),
+        }
+    }
+}
+
+/// Error signing with an [`L1Signer`].
+#[derive(Debug, Display, From)]
+pub enum L1SignerError {
+    Local(WalletError),
+    #[cfg(feature = "ledger")]
+    Ledger(LedgerError),
+    #[cfg(feature = "aws-kms")]
+    Aws(AwsSignerError),
+}
+
+impl std::error::Error for L1SignerError {}
+
+#[async_trait]
+impl Signer for L1Signer {
+    type Error = L1SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        Ok(match &*self.inner {
+            Inner::Local(wallet) => wallet.sign_message(message).await?,
+            #[cfg(feature = "ledger")]
+            Inner::Ledger(ledger) => ledger.sign_message(message).await?,
+            #[cfg(feature = "aws-kms")]
+            Inner::Aws(kms) => kms.sign_message(message).await?,
+        })
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        // The underlying signer may have been created for a different chain.
+        let mut tx = tx.clone();
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id);
+        }
+        Ok(match &*self.inner {
+            Inner::Local(wallet) => wallet.sign_transaction(&tx).await?,
+            #[cfg(feature = "ledger")]
+            Inner::Ledger(ledger) => ledger.sign_transaction(&tx).await?,
+            #[cfg(feature = "aws-kms")]
+            Inner::Aws(kms) => kms.sign_transaction(&tx).await?,
+        })
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        Ok(match &*self.inner {
+            Inner::Local(wallet) => wallet.sign_typed_data(payload).await?,
+            #[cfg(feature = "ledger")]
+            Inner::Ledger(ledger) => ledger.sign_typed_data(payload).await?,
+            #[cfg(feature = "aws-kms")]
+            Inner::Aws(kms) => kms.sign_typed_data(payload).await?,
+        })
+    }
+
+    fn address(&self) -> Address {
+        match &*self.inner {
+            Inner::Local(wallet) => wallet.address(),
+            #[cfg(feature = "ledger")]
+            Inner::Ledger(ledger) => ledger.address(),
+            #[cfg(feature = "aws-kms")]
+            Inner::Aws(kms) => kms.address(),
+        }
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn with_chain_id<T: Into<u64>>(mut self, chain_id: T) -> Self {
+        self.chain_id = chain_id.into();
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ethers::types::TransactionRequest;
+
+    #[async_std::test]
+    async fn test_l1_signer_chain_id() {
+        let source = L1SignerSource::Mnemonic {
+            phrase: "test test test test test test test test test test test junk".into(),
+            index: 0,
+        };
+        let signer = source.connect(1).await.unwrap();
+        let wallet = MnemonicBuilder::<English>::default()
+            .phrase("test test test test test test test test test test test junk")
+            .build()
+            .unwrap();
+        assert_eq!(signer.address(), wallet.address());
+
+        // Changing the chain of the shared signer signs for the new chain.
+        let signer = signer.with_chain_id(5u64);
+        let tx: TypedTransaction = TransactionRequest::new().into();
+        let sig = signer.sign_transaction(&tx).await.unwrap();
+        let expected = wallet
+            .with_chain_id(5u64)
+            .sign_transaction(&tx)
+            .await
+            .unwrap();
+        assert_eq!(sig, expected);
+    }
+}